@@ -1,19 +1,207 @@
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
 use std::env;
-use std::fs::{read_to_string, File};
+use std::ffi::OsString;
+use std::fs::{create_dir_all, read, read_to_string, write, File};
+use std::hash::{Hash, Hasher};
 use std::io::{BufWriter, Write};
+use std::iter::once;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 use lazy_static::lazy_static;
 use shaderc::{
-    CompileOptions, Compiler, IncludeCallbackResult, IncludeType, OptimizationLevel,
-    ResolvedInclude, ShaderKind,
+    CompileOptions, Compiler, EnvVersion, GlslProfile, IncludeCallbackResult, IncludeType,
+    OptimizationLevel, ResolvedInclude, ShaderKind, TargetEnv,
 };
 
 #[derive(Debug)]
 struct Shader {
     file: PathBuf,
     shader_type: ShaderKind,
-    filename: String, // relative to root folder, but can be arbitrary
+    filename: String, // relative to root folder, but can be arbitrary, see variant_name
+    options: ShaderOptions,
+    defines: Vec<String>, // "NAME" or "NAME=value", sorted
+    entry: Option<String>,
+}
+
+// "<file>:<entry>", the plain file name for the main of a .frag, .vert or .comp
+fn entry_name(filename: &str, entry: Option<&str>) -> String {
+    match entry {
+        Some(entry) => format!("{}:{}", filename, entry),
+        None => filename.to_owned(),
+    }
+}
+
+// the key of a variant in the shader map, must match crate::shaders::variant_name
+// eg: "draw_agents.comp" or "draw_agents.comp#FAST,SPECIES=4"
+fn variant_name(filename: &str, defines: &[String]) -> String {
+    if defines.is_empty() {
+        filename.to_owned()
+    } else {
+        format!("{}#{}", filename, defines.join(","))
+    }
+}
+
+// the defaults depend on the cargo profile, see Header for setting them per shader
+#[derive(Clone, Debug)]
+struct ShaderOptions {
+    optimize: OptimizationLevel,
+    debug_info: bool,
+    version: Option<u32>,
+    target_env: Option<EnvVersion>,
+}
+
+impl ShaderOptions {
+    fn new() -> Self {
+        let release = env::var("PROFILE").map_or(false, |profile| profile == "release");
+
+        Self {
+            optimize: if release {
+                OptimizationLevel::Performance
+            } else {
+                OptimizationLevel::Zero
+            },
+            debug_info: !release,
+            version: None,
+            target_env: None,
+        }
+    }
+
+    // deps collects every file resolved by include_lygia, including nested ones
+    fn compile_options(
+        &self,
+        defines: &[String],
+        entry: Option<&str>,
+        deps: &Rc<RefCell<BTreeSet<PathBuf>>>,
+    ) -> CompileOptions<'static> {
+        let mut options = CompileOptions::new().unwrap();
+        options.set_optimization_level(self.optimize);
+        options.set_warnings_as_errors();
+
+        if self.debug_info {
+            options.set_generate_debug_info();
+        }
+        if let Some(version) = self.version {
+            options.set_forced_version_profile(version, GlslProfile::None);
+        }
+        if let Some(env) = self.target_env {
+            options.set_target_env(TargetEnv::Vulkan, env as u32);
+        }
+
+        for define in defines {
+            match define.split_once('=') {
+                Some((name, value)) => options.add_macro_definition(name, Some(value)),
+                None => options.add_macro_definition(define, None),
+            }
+        }
+
+        // only ENTRY_<name>, so that the name can still be used for anything else in the file
+        if let Some(entry) = entry {
+            options.add_macro_definition(&format!("ENTRY_{}", entry), None);
+        }
+
+        let deps = Rc::clone(deps);
+        options.set_include_callback(move |include, ty, source, depth| {
+            let resolved = include_lygia(include, ty, source, depth)?;
+            deps.borrow_mut()
+                .insert(PathBuf::from(&resolved.resolved_name));
+            Ok(resolved)
+        });
+
+        options
+    }
+}
+
+// "//! <key>: <value>" lines of a glsl file
+//   //! optimize: zero | size | performance
+//   //! debug_info: true | false
+//   //! version: 460 (overrides #version)
+//   //! target_env: vulkan1.0 | vulkan1.1 | vulkan1.2
+//   //! variant: SPECIES=4 FAST (compiled in addition to the plain shader, one variant per line)
+//   //! entry: compute diffuse
+struct Header {
+    options: ShaderOptions,
+    variants: Vec<Vec<String>>, // the defines of each variant, the first one is the plain shader
+    // "//! entry: <vertex | fragment | compute> <name>", each one is compiled as its own shader
+    // with ENTRY_<name> defined, the file has a main per entry in #ifdef ENTRY_<name>, along with
+    // anything else only that entry uses, eg: its local_size or bindings
+    // so that related passes can share a file and its structs
+    entries: Vec<(ShaderKind, String)>,
+}
+
+impl Header {
+    fn parse(file: &Path, source: &str) -> Self {
+        let mut options = ShaderOptions::new();
+        let mut variants = vec![Vec::new()];
+        let mut entries = Vec::new();
+
+        for line in source.lines().map(str::trim) {
+            let directive = match line.strip_prefix("//!") {
+                Some(directive) => directive,
+                None => continue,
+            };
+
+            let valid = directive.split_once(':').and_then(|(key, value)| {
+                let value = value.trim();
+
+                match key.trim() {
+                    "optimize" => {
+                        options.optimize = match value {
+                            "zero" => OptimizationLevel::Zero,
+                            "size" => OptimizationLevel::Size,
+                            "performance" => OptimizationLevel::Performance,
+                            _ => return None,
+                        }
+                    }
+                    "debug_info" => options.debug_info = value.parse().ok()?,
+                    "version" => options.version = Some(value.parse().ok()?),
+                    "target_env" => {
+                        options.target_env = Some(match value {
+                            "vulkan1.0" => EnvVersion::Vulkan1_0,
+                            "vulkan1.1" => EnvVersion::Vulkan1_1,
+                            "vulkan1.2" => EnvVersion::Vulkan1_2,
+                            _ => return None,
+                        })
+                    }
+                    "variant" => {
+                        let mut defines: Vec<_> =
+                            value.split_whitespace().map(str::to_owned).collect();
+                        defines.sort();
+                        variants.push(defines);
+                    }
+                    "entry" => {
+                        let (stage, name) = value.split_once(char::is_whitespace)?;
+                        let stage = match stage {
+                            "vertex" => ShaderKind::Vertex,
+                            "fragment" => ShaderKind::Fragment,
+                            "compute" => ShaderKind::Compute,
+                            _ => return None,
+                        };
+                        entries.push((stage, name.trim().to_owned()));
+                    }
+                    _ => return None,
+                }
+
+                Some(())
+            });
+
+            if valid.is_none() {
+                println!(
+                    "cargo:warning={}: ignoring invalid directive {:?}",
+                    file.display(),
+                    line
+                );
+            }
+        }
+
+        Self {
+            options,
+            variants,
+            entries,
+        }
+    }
 }
 
 struct Error(String);
@@ -26,10 +214,109 @@ impl From<std::io::Error> for Error {
 
 impl From<shaderc::Error> for Error {
     fn from(err: shaderc::Error) -> Self {
-        Self(format!("Compile Error: {}", err))
+        match err {
+            // glslang's log, one "<file>:<line>: error: <message>" per line
+            shaderc::Error::CompilationError(_, log) => Self(log),
+            err => Self(format!("Compile Error: {}", err)),
+        }
     }
 }
 
+// formats glslang's log as "<file>:<line>:<column>: <message>" followed by the source line
+// the file is the one containing the error, since includes are resolved with their own name
+// the column points at the quoted token of the message, if it can be found on the line
+fn report(info: &Shader, log: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for line in log.lines() {
+        let (location, message) = match line.split_once(": error: ") {
+            Some(split) => split,
+            None if line.ends_with("generated.") => continue, // "1 error generated."
+            None => {
+                lines.push(format!("{}: {}", info.filename, line));
+                continue;
+            }
+        };
+
+        let (file, line_number) = match location.rsplit_once(':') {
+            Some((file, line_number)) => match line_number.parse::<usize>() {
+                Ok(line_number) => (ROOT.join(file), line_number),
+                Err(_) => (info.file.clone(), 0),
+            },
+            None => (info.file.clone(), 0),
+        };
+
+        let source_line = read_to_string(&file).ok().and_then(|source| {
+            source
+                .lines()
+                .nth(line_number.checked_sub(1)?)
+                .map(str::to_owned)
+        });
+
+        let token = message.split('\'').nth(1).filter(|token| !token.is_empty());
+        let column = match (&source_line, token) {
+            (Some(source_line), Some(token)) => source_line.find(token).map_or(1, |i| i + 1),
+            _ => 1,
+        };
+
+        let file = file.strip_prefix(*REPO_ROOT).unwrap_or(&file);
+        lines.push(format!(
+            "{}:{}:{}: error: {}",
+            file.display(),
+            line_number,
+            column,
+            message
+        ));
+
+        if let Some(source_line) = source_line {
+            let gutter = line_number.to_string().len();
+            lines.push(format!("{} | {}", line_number, source_line));
+            lines.push(format!(
+                "{:gutter$} | {:>column$}",
+                "",
+                "^",
+                gutter = gutter,
+                column = column
+            ));
+        }
+    }
+
+    lines
+}
+
+// wgsl is embedded as source, since wgpu compiles it itself, so it is only validated here
+// it has no preprocessor, so there are no includes, options or variants
+// every entry point is loaded as "<file>:<entry>", and "<file>" loads the one named main
+// returns the source and the workgroup size of each compute entry point
+fn validate_wgsl(file: &Path) -> Result<(String, Vec<(String, [u32; 3])>), Error> {
+    let source = read_to_string(file)?;
+
+    let module = naga::front::wgsl::parse_str(&source).map_err(|err| Error(err.to_string()))?;
+    naga::valid::Validator::new(naga::valid::ValidationFlags::all())
+        .validate(&module)
+        .map_err(|err| Error(err.to_string()))?;
+
+    let workgroup_sizes = module
+        .entry_points
+        .iter()
+        .filter(|entry| entry.stage == naga::ShaderStage::Compute)
+        .map(|entry| (entry.name.clone(), entry.workgroup_size))
+        .collect();
+
+    Ok((source, workgroup_sizes))
+}
+
+// prints the report as warnings and returns the code of the map entry
+fn error_code(lines: Vec<String>, errors: &mut Vec<Vec<String>>) -> String {
+    for line in &lines {
+        println!("cargo:warning={}", line);
+    }
+
+    let code = format!("Err({:?})", lines.join("\n"));
+    errors.push(lines);
+    code
+}
+
 fn dbg(x: impl std::fmt::Debug) {
     println!("cargo:warning={:?}", x);
 }
@@ -39,6 +326,85 @@ lazy_static! {
     static ref ROOT: PathBuf = REPO_ROOT.join("src/shaders");
     static ref STD_ROOT: PathBuf = ROOT.join("std");
     static ref LYGIA_ROOT: PathBuf = REPO_ROOT.join("lygia");
+    static ref CACHE_ROOT: PathBuf =
+        Path::new(&env::var_os("OUT_DIR").unwrap()).join("shader_cache");
+}
+
+// bump this when the way shaders are compiled changes, to invalidate every cached shader
+const CACHE_VERSION: u32 = 1;
+
+// the spirv of a shader, cached in OUT_DIR next to a manifest with the hash of the sources it was
+// compiled from and the files it included, so that unchanged shaders are not recompiled
+struct CacheEntry {
+    manifest: PathBuf,
+    spirv: PathBuf,
+}
+
+impl CacheEntry {
+    fn new(info: &Shader) -> Self {
+        let path = |ext: &str| {
+            // ':' isn't allowed in file names on windows
+            let mut path = OsString::from(CACHE_ROOT.join(info.filename.replace(':', "@")));
+            path.push(ext);
+            PathBuf::from(path)
+        };
+
+        Self {
+            manifest: path(".deps"),
+            spirv: path(".spv"),
+        }
+    }
+
+    // hashes the shader and its includes, None if one of them can't be read anymore
+    fn key(info: &Shader, deps: &[PathBuf]) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+
+        CACHE_VERSION.hash(&mut hasher);
+        format!("{:?}", info.shader_type).hash(&mut hasher);
+        format!("{:?}", info.options).hash(&mut hasher);
+        info.defines.hash(&mut hasher);
+        info.entry.hash(&mut hasher);
+
+        for path in once(&info.file).chain(deps) {
+            path.hash(&mut hasher);
+            read(path).ok()?.hash(&mut hasher);
+        }
+
+        Some(hasher.finish())
+    }
+
+    // the cached spirv and includes, if none of the sources changed since it was stored
+    fn load(&self, info: &Shader) -> Option<(Vec<u8>, Vec<PathBuf>)> {
+        let manifest = read_to_string(&self.manifest).ok()?;
+        let mut lines = manifest.lines();
+
+        let key = u64::from_str_radix(lines.next()?, 16).ok()?;
+        let deps: Vec<_> = lines.map(PathBuf::from).collect();
+
+        if Self::key(info, &deps)? != key {
+            return None;
+        }
+
+        Some((read(&self.spirv).ok()?, deps))
+    }
+
+    fn store(&self, info: &Shader, deps: &[PathBuf], spirv: &[u8]) -> std::io::Result<()> {
+        let key = match Self::key(info, deps) {
+            Some(key) => key,
+            None => return Ok(()), // changed while compiling, will be compiled again next time
+        };
+
+        let mut manifest = format!("{:016x}\n", key);
+        for dep in deps {
+            manifest.push_str(&dep.to_string_lossy());
+            manifest.push('\n');
+        }
+
+        create_dir_all(self.manifest.parent().unwrap())?;
+        // spirv first, so that a manifest never points to a stale spirv
+        write(&self.spirv, spirv)?;
+        write(&self.manifest, manifest)
+    }
 }
 
 // since this takes &str, it probably isnt OsStr safe
@@ -83,26 +449,69 @@ fn include_lygia(
 
 fn main() {
     // allow utf8 paths everywhere except filename and extention
-    let queue: Vec<_> = walkdir::WalkDir::new(&*ROOT)
+    let files: Vec<_> = walkdir::WalkDir::new(&*ROOT)
         .follow_links(true)
         .into_iter()
         .map(|entry| entry.unwrap())
         .filter(|entry| entry.file_type().is_file())
         .filter_map(|file| {
             let file = file.into_path();
-            let shader_type = match file.extension()?.to_str()? {
-                "frag" => ShaderKind::Fragment,
-                "vert" => ShaderKind::Vertex,
-                "comp" => ShaderKind::Compute,
-                _ => return None,
-            };
+            let extension = file.extension()?.to_str()?.to_owned();
+            if !["frag", "vert", "comp", "glsl", "wgsl"].contains(&extension.as_str()) {
+                return None;
+            }
             // let filename = file.file_name()?.to_str()?.to_owned();
             let filename = file.strip_prefix(&*ROOT).unwrap().to_str()?.to_owned();
 
-            Some(Shader {
-                file,
-                shader_type,
-                filename,
+            Some((file, extension, filename))
+        })
+        .collect();
+
+    let (wgsl, glsl): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .partition(|(_, extension, _)| extension == "wgsl");
+
+    // one entry per entry point and variant, an unreadable file is reported when compiling it
+    // .glsl files are only compiled if they declare entry points, the rest are includes
+    let queue: Vec<_> = glsl
+        .into_iter()
+        .flat_map(|(file, extension, filename)| {
+            let source = read_to_string(&file).unwrap_or_default();
+            let header = Header::parse(&file, &source);
+
+            let entries: Vec<_> = if header.entries.is_empty() {
+                let stage = match extension.as_str() {
+                    "frag" => Some(ShaderKind::Fragment),
+                    "vert" => Some(ShaderKind::Vertex),
+                    "comp" => Some(ShaderKind::Compute),
+                    _ => None,
+                };
+
+                stage.into_iter().map(|stage| (stage, None)).collect()
+            } else {
+                header
+                    .entries
+                    .into_iter()
+                    .map(|(stage, entry)| (stage, Some(entry)))
+                    .collect()
+            };
+
+            let options = header.options;
+            let variants = header.variants;
+
+            entries.into_iter().flat_map(move |(shader_type, entry)| {
+                let name = entry_name(&filename, entry.as_deref());
+                let file = file.clone();
+                let options = options.clone();
+
+                variants.clone().into_iter().map(move |defines| Shader {
+                    file: file.clone(),
+                    shader_type,
+                    filename: variant_name(&name, &defines),
+                    options: options.clone(),
+                    defines,
+                    entry: entry.clone(),
+                })
             })
         })
         .collect();
@@ -111,22 +520,22 @@ fn main() {
     //     .lines()
     //     .for_each(|line| println!("cargo:warning={}", line));
 
-    let mut options = CompileOptions::new().unwrap();
-    options.set_optimization_level(OptimizationLevel::Zero);
-    options.set_warnings_as_errors();
-    options.set_include_callback(include_lygia);
-    let options = Some(&options);
+    // the files included while compiling the current shader
+    let deps = Rc::new(RefCell::new(BTreeSet::new()));
 
     let mut compiler = Compiler::new().unwrap();
 
     let mut compile = |info: &Shader| -> Result<_, Error> {
         let source = read_to_string(&info.file)?;
+        let options = info
+            .options
+            .compile_options(&info.defines, info.entry.as_deref(), &deps);
         let artifact = compiler.compile_into_spirv(
             &source,
             info.shader_type,
             &info.file.as_path().to_string_lossy(),
             "main",
-            options,
+            Some(&options),
         )?;
 
         Ok(artifact)
@@ -134,15 +543,79 @@ fn main() {
 
     let mut codegen = phf_codegen::Map::<&str>::new();
 
+    // the whole shader folder for new and deleted files, lygia includes are added per file
+    let mut rerun: BTreeSet<PathBuf> = BTreeSet::new();
+    let mut errors = Vec::new();
+    rerun.insert(REPO_ROOT.join("build.rs"));
+    rerun.insert(ROOT.clone());
+
     for info in queue.iter() {
-        let code = match compile(info) {
-            Ok(artifact) => format!("Ok(&{:?})", artifact.as_binary_u8()),
-            Err(err) => format!("Err({:?})", err.0),
+        let entry = CacheEntry::new(info);
+
+        let result = match entry.load(info) {
+            Some((spirv, included)) => {
+                rerun.extend(included);
+                Ok(spirv)
+            }
+            None => {
+                deps.borrow_mut().clear();
+                let result = compile(info).map(|artifact| artifact.as_binary_u8().to_owned());
+                let included: Vec<_> = deps.borrow().iter().cloned().collect();
+
+                // errors aren't cached, they are compiled again on the next build
+                if let Ok(spirv) = &result {
+                    if let Err(err) = entry.store(info, &included, spirv) {
+                        dbg(format!("Could not cache {}: {}", info.filename, err));
+                    }
+                }
+
+                rerun.extend(included);
+                result
+            }
+        };
+
+        let code = match result {
+            Ok(spirv) => format!("Ok(Shader::Spirv(&{:?}))", spirv),
+            Err(err) => error_code(report(info, &err.0), &mut errors),
         };
 
         codegen.entry(&info.filename, &code);
     }
 
+    for (file, _, filename) in wgsl.iter() {
+        let code = match validate_wgsl(file) {
+            Ok((source, workgroup_sizes)) => format!(
+                "Ok(Shader::Wgsl {{ source: {:?}, workgroup_sizes: &{:?} }})",
+                source, workgroup_sizes
+            ),
+            Err(err) => {
+                let path = file.strip_prefix(*REPO_ROOT).unwrap_or(file);
+                let lines = err
+                    .0
+                    .lines()
+                    .map(|line| format!("{}: error: {}", path.display(), line))
+                    .collect();
+
+                error_code(lines, &mut errors)
+            }
+        };
+
+        codegen.entry(filename, &code);
+    }
+
+    for path in rerun {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+
+    // cargo build --features strict_shaders
+    if env::var_os("CARGO_FEATURE_STRICT_SHADERS").is_some() && !errors.is_empty() {
+        for line in errors.iter().flatten() {
+            eprintln!("{}", line);
+        }
+
+        panic!("{} shaders failed to compile", errors.len());
+    }
+
     let path = Path::new(&env::var_os("OUT_DIR").unwrap()).join("codegen_shaders.rs");
     dbg(&path);
 
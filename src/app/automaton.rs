@@ -0,0 +1,633 @@
+use ::wgpu::{CommandEncoder, RenderPass, StorageTextureAccess, TextureFormat, TextureUsage};
+use crevice::std430::{AsStd430, Std430};
+use imgui::{im_str, Drag, ImString, SliderFlags, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::imgui::ImguiWgpuRender;
+use crate::input::PointerInput;
+use crate::keymap::{Action, ActionInput};
+use crate::random;
+use crate::serialize;
+use crate::util::{CreateFromWgpu, InitType, SamplerDesc, TextureDesc};
+use crate::wgpu::{
+    begin_render_pass, BindGroupEntry, ColorAttachment, ComputePipelineDesc, DispatchExt,
+    FullComputePipeline, FullRenderPipeline, PipelineExt, RenderPipelineDesc, TextureResult,
+    WgpuBase, WgpuWindowed, WgpuWindowedRender,
+};
+
+use super::gradient::{Colormap, Gradient, GradientLut};
+use super::pattern::Pattern;
+use super::post::{self, EffectSlot, PostStack};
+use super::registry::{App, PAUSE, RESET, STEP};
+use super::rule::Rule;
+
+// cells are a few pixels wide when stretched over the window
+const WIDTH: u32 = 1920 / 4;
+const HEIGHT: u32 = 1015 / 4;
+
+const DISCRETE_DESC: TextureDesc = TextureDesc {
+    format: TextureFormat::R8Uint,
+    width: WIDTH,
+    height: HEIGHT,
+};
+
+const LENIA_DESC: TextureDesc = TextureDesc {
+    format: TextureFormat::R32Float,
+    width: WIDTH,
+    height: HEIGHT,
+};
+
+const GLIDER_GUN: &str = "#N Gosper glider gun
+x = 36, y = 9, rule = B3/S23
+24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4b
+obo$10bo5bo7bo$11bo3bo$12b2o!";
+
+// name, rule string
+const RULES: [(&str, &str); 5] = [
+    ("Life", "B3/S23"),
+    ("HighLife", "B36/S23"),
+    ("Day & Night", "B3678/S34678"),
+    ("Brian's Brain", "B2/S/C3"),
+    ("Star Wars", "345/2/4"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+enum Mode {
+    Discrete, // life-like and generations rules
+    Lenia,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct LeniaParams {
+    radius: u32,
+    mu: f32,
+    sigma: f32,
+    delta_time: f32,
+}
+
+impl Default for LeniaParams {
+    // orbium, https://arxiv.org/abs/1812.05433
+    fn default() -> Self {
+        Self {
+            radius: 13,
+            mu: 0.15,
+            sigma: 0.015,
+            delta_time: 0.1,
+        }
+    }
+}
+
+#[derive(AsStd430, Debug)]
+struct DiscreteConstants {
+    birth: u32,
+    survive: u32,
+    states: u32,
+}
+
+#[derive(AsStd430, Debug)]
+struct LeniaConstants {
+    radius: u32,
+    mu: f32,
+    sigma: f32,
+    delta_time: f32,
+}
+
+#[derive(AsStd430, Debug)]
+struct RenderConstants {
+    states: u32,
+}
+
+// everything that can be tweaked from imgui, saved and loaded with serialize.rs
+#[derive(Serialize, Deserialize, Debug)]
+struct Preset {
+    mode: Mode,
+    rule: Rule,
+    lenia: LeniaParams,
+    steps: u32,
+    gradient: Gradient,
+    post: Vec<EffectSlot>,
+}
+
+// waiting to be written into the current grid
+enum Init {
+    Pattern(Pattern),
+    Soup(u32), // seed
+    Clear,
+}
+
+// uniform noise from 0 to 1 in the central quarter of the grid, 0 elsewhere
+fn soup(seed: u32) -> Vec<f32> {
+    (0..HEIGHT)
+        .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let inside =
+                (x * 4 / WIDTH).wrapping_sub(1) < 2 && (y * 4 / HEIGHT).wrapping_sub(1) < 2;
+
+            if inside {
+                random::rand([x, y, 0, 0], seed, 0)[0] as f32 / u32::MAX as f32
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+// ping pong textures with a step pipeline and a render pipeline for each direction
+struct Grid {
+    textures: [TextureResult; 2],
+    step_pipelines: [FullComputePipeline; 2], // indexed by which texture is read
+    render_pipelines: [FullRenderPipeline; 2],
+    current: usize, // which texture holds the latest state
+}
+
+impl Grid {
+    fn new(
+        wgpu_base: &mut WgpuBase,
+        desc: &TextureDesc,
+        step: &'static str,
+        push_constants: u32,
+        render: &'static str,
+        lut: &GradientLut,
+    ) -> Self {
+        let desc =
+            desc.into_2d(TextureUsage::COPY_DST | TextureUsage::SAMPLED | TextureUsage::STORAGE);
+
+        let textures = [
+            wgpu_base.texture(&desc, InitType::Zeros),
+            wgpu_base.texture(&desc, InitType::Zeros),
+        ];
+
+        let storage = |access, index: usize| BindGroupEntry::Texture {
+            storage: Some(access),
+            desc: desc.clone(),
+            view: &textures[index].view,
+        };
+
+        let mut step_pipeline = |source: usize| {
+            let bind_group = wgpu_base.bind_group(&[
+                storage(StorageTextureAccess::ReadOnly, source),
+                storage(StorageTextureAccess::WriteOnly, 1 - source),
+            ]);
+
+            wgpu_base.compute_pipeline(ComputePipelineDesc {
+                bind_groups: vec![bind_group],
+                shader: step,
+                defines: &[],
+                push_constants: Some(push_constants),
+            })
+        };
+        let step_pipelines = [step_pipeline(0), step_pipeline(1)];
+
+        // the state is read with texelFetch, integer textures can't be filtered anyway
+        let mut render_pipeline = |source: usize| {
+            let bind_group = wgpu_base.bind_group(&[
+                BindGroupEntry::Texture {
+                    storage: None,
+                    desc: desc.clone(),
+                    view: &textures[source].view,
+                },
+                BindGroupEntry::Texture {
+                    storage: None,
+                    desc: lut.texture.desc.clone(),
+                    view: &lut.texture.view,
+                },
+                BindGroupEntry::Sampler {
+                    desc: SamplerDesc {
+                        filter: true,
+                        ..Default::default()
+                    },
+                },
+            ]);
+
+            wgpu_base.render_pipeline(RenderPipelineDesc {
+                bind_groups: vec![bind_group],
+                shader: render,
+                targets: vec![post::FORMAT.into()],
+                push_constants: Some(RenderConstants::std430_size_static() as _),
+            })
+        };
+        let render_pipelines = [render_pipeline(0), render_pipeline(1)];
+
+        Self {
+            textures,
+            step_pipelines,
+            render_pipelines,
+            current: 0,
+        }
+    }
+}
+
+pub struct Automaton {
+    discrete: Grid,
+    lenia_grid: Grid,
+    mode: Mode,
+    rule: Rule,
+    rule_text: ImString,
+    lenia: LeniaParams,
+    running: bool,
+    step: bool, // advance once while paused
+    steps: u32, // per frame while running
+    path: ImString,
+    path_error: Option<String>, // of the last load
+    init: Option<Init>,
+    seed: u32,
+    lut: GradientLut,
+    post: PostStack,
+}
+
+impl CreateFromWgpu for Automaton {
+    fn new(wgpu_base: &mut WgpuBase, swapchain_desc: &TextureDesc) -> Self {
+        let lut = GradientLut::new(
+            wgpu_base,
+            Gradient {
+                colormap: Colormap::Inferno,
+                stops: Vec::new(),
+            },
+        );
+
+        let discrete = Grid::new(
+            wgpu_base,
+            &DISCRETE_DESC,
+            "automaton/step.comp",
+            DiscreteConstants::std430_size_static() as _,
+            "automaton/render_discrete.frag",
+            &lut,
+        );
+
+        let lenia_grid = Grid::new(
+            wgpu_base,
+            &LENIA_DESC,
+            "automaton/lenia.comp",
+            LeniaConstants::std430_size_static() as _,
+            "automaton/render_lenia.frag",
+            &lut,
+        );
+
+        let post = PostStack::new(wgpu_base, &LENIA_DESC, swapchain_desc.format);
+
+        let mut app = Self {
+            discrete,
+            lenia_grid,
+            mode: Mode::Discrete,
+            rule: Rule::LIFE,
+            rule_text: ImString::default(),
+            lenia: Default::default(),
+            running: true,
+            step: false,
+            steps: 1,
+            path: ImString::with_capacity(256),
+            path_error: None,
+            init: Pattern::parse_rle(GLIDER_GUN).map(Init::Pattern),
+            seed: 0,
+            lut,
+            post,
+        };
+
+        app.set_rule(Rule::LIFE);
+        app
+    }
+}
+
+impl Automaton {
+    fn preset(&self) -> Preset {
+        Preset {
+            mode: self.mode,
+            rule: self.rule,
+            lenia: self.lenia,
+            steps: self.steps,
+            gradient: self.lut.gradient().clone(),
+            post: self.post.effects.clone(),
+        }
+    }
+
+    fn set_preset(&mut self, preset: Preset) {
+        self.mode = preset.mode;
+        self.set_rule(preset.rule);
+        self.lenia = preset.lenia;
+        self.steps = preset.steps;
+        self.lut.set(preset.gradient);
+        self.post.effects = preset.post;
+    }
+
+    fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        self.rule_text = ImString::with_capacity(64);
+        self.rule_text.push_str(&rule.to_string());
+    }
+
+    fn grid(&mut self) -> &mut Grid {
+        match self.mode {
+            Mode::Discrete => &mut self.discrete,
+            Mode::Lenia => &mut self.lenia_grid,
+        }
+    }
+
+    // texels of the current mode's format
+    fn init_data(&self, init: &Init) -> Vec<u8> {
+        let states = self.rule.states;
+
+        match (self.mode, init) {
+            (Mode::Discrete, Init::Pattern(pattern)) => pattern
+                .place(WIDTH, HEIGHT)
+                .into_iter()
+                .map(|cell| {
+                    if pattern.grayscale {
+                        (cell > 127) as u8
+                    } else {
+                        cell.min((states - 1) as u8)
+                    }
+                })
+                .collect(),
+            (Mode::Lenia, Init::Pattern(pattern)) => {
+                let cells: Vec<f32> = pattern
+                    .place(WIDTH, HEIGHT)
+                    .into_iter()
+                    .map(|cell| {
+                        if pattern.grayscale {
+                            cell as f32 / 255.0
+                        } else {
+                            (cell == 1) as u8 as f32
+                        }
+                    })
+                    .collect();
+
+                bytemuck::cast_slice(&cells).to_vec()
+            }
+            (Mode::Discrete, Init::Soup(seed)) => soup(*seed)
+                .into_iter()
+                .map(|value| (value > 0.75) as u8)
+                .collect(),
+            (Mode::Lenia, Init::Soup(seed)) => bytemuck::cast_slice(&soup(*seed)).to_vec(),
+            (Mode::Discrete, Init::Clear) => vec![0; (WIDTH * HEIGHT) as usize],
+            (Mode::Lenia, Init::Clear) => vec![0; (WIDTH * HEIGHT) as usize * 4],
+        }
+    }
+
+    fn load_pattern(&mut self) {
+        let pattern = match Pattern::load(self.path.to_str()) {
+            Ok(pattern) => pattern,
+            Err(err) => {
+                self.path_error = Some(err);
+                return;
+            }
+        };
+
+        if let Some(rule) = pattern.rule {
+            self.set_rule(rule);
+        }
+
+        self.init = Some(Init::Pattern(pattern));
+        self.path_error = None;
+    }
+}
+
+impl WgpuWindowedRender for Automaton {
+    fn render<'a>(&'a mut self, _: &WgpuWindowed<'_>, render_pass: &mut RenderPass<'a>) {
+        self.post.render(render_pass);
+    }
+
+    fn render_encoder(
+        &mut self,
+        wgpu_windowed: &WgpuWindowed<'_>,
+        encoder: &mut CommandEncoder,
+        after: bool,
+    ) {
+        if after {
+            return;
+        }
+
+        let base = &wgpu_windowed.base;
+
+        self.lut.update(base);
+
+        if let Some(init) = self.init.take() {
+            let data = self.init_data(&init);
+            let grid = self.grid();
+            base.write_texture(&grid.textures[grid.current], &data);
+        }
+
+        let steps = if self.running {
+            self.steps
+        } else {
+            self.step as u32
+        };
+        self.step = false;
+
+        let constants = match self.mode {
+            Mode::Discrete => {
+                let Rule {
+                    birth,
+                    survive,
+                    states,
+                } = self.rule;
+
+                DiscreteConstants {
+                    birth,
+                    survive,
+                    states,
+                }
+                .as_std430()
+                .as_bytes()
+                .to_vec()
+            }
+            Mode::Lenia => {
+                let LeniaParams {
+                    radius,
+                    mu,
+                    sigma,
+                    delta_time,
+                } = self.lenia;
+
+                LeniaConstants {
+                    radius,
+                    mu,
+                    sigma,
+                    delta_time,
+                }
+                .as_std430()
+                .as_bytes()
+                .to_vec()
+            }
+        };
+
+        let render_constants = RenderConstants {
+            states: self.rule.states,
+        };
+
+        let input = self.post.input();
+        let grid = match self.mode {
+            Mode::Discrete => &mut self.discrete,
+            Mode::Lenia => &mut self.lenia_grid,
+        };
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&Default::default());
+
+            for _ in 0..steps {
+                let pipeline = &grid.step_pipelines[grid.current];
+                compute_pass.begin(pipeline);
+                compute_pass.pushc(&constants);
+                compute_pass.dispatch_for_texture(pipeline, &DISCRETE_DESC);
+
+                grid.current = 1 - grid.current;
+            }
+        }
+
+        {
+            let mut render_pass = begin_render_pass(encoder, &[ColorAttachment::new(input)]);
+            render_pass.begin(&grid.render_pipelines[grid.current]);
+            render_pass.pushc(render_constants.as_std430().as_bytes());
+            render_pass.draw(0..3, 0..1);
+        }
+
+        self.post.encode(encoder);
+    }
+}
+
+impl ImguiWgpuRender for Automaton {
+    fn render_ui(&mut self, ui: &mut imgui::Ui<'_>) {
+        let mut rule = None; // from the buttons, also replaces the text
+        let mut load = false;
+
+        let mode = &mut self.mode;
+        let current_rule = &mut self.rule;
+        let rule_text = &mut self.rule_text;
+        let lenia = &mut self.lenia;
+        let running = &mut self.running;
+        let step = &mut self.step;
+        let steps = &mut self.steps;
+        let path = &mut self.path;
+        let path_error = &self.path_error;
+        let init = &mut self.init;
+        let seed = &mut self.seed;
+        let lut = &mut self.lut;
+
+        Window::new(im_str!("Automaton"))
+            .always_auto_resize(true)
+            .build(ui, || {
+                ui.radio_button(im_str!("Discrete"), mode, Mode::Discrete);
+                ui.same_line(0.0);
+                ui.radio_button(im_str!("Lenia"), mode, Mode::Lenia);
+
+                ui.checkbox(im_str!("Running"), running);
+                ui.same_line(0.0);
+                *step |= ui.small_button(im_str!("Step"));
+                Drag::new(im_str!("Steps per Frame"))
+                    .range(1..=64)
+                    .build(ui, steps);
+                ui.separator();
+
+                match *mode {
+                    Mode::Discrete => {
+                        ui.input_text(im_str!("Rule"), rule_text).build();
+                        match Rule::parse(rule_text.to_str()) {
+                            Some(parsed) => *current_rule = parsed,
+                            None => ui.text(im_str!("invalid rule")),
+                        }
+
+                        for (index, (name, text)) in RULES.iter().enumerate() {
+                            if index % 3 != 0 {
+                                ui.same_line(0.0);
+                            }
+                            if ui.small_button(&im_str!("{}", name)) {
+                                rule = Rule::parse(text);
+                            }
+                        }
+                    }
+                    Mode::Lenia => {
+                        let LeniaParams {
+                            radius,
+                            mu,
+                            sigma,
+                            delta_time,
+                        } = lenia;
+
+                        Drag::new(im_str!("Radius")).range(1..=32).build(ui, radius);
+                        Drag::new(im_str!("Mu"))
+                            .range(0.0..=1.0)
+                            .speed(0.001)
+                            .display_format(im_str!("%.4f"))
+                            .build(ui, mu);
+                        Drag::new(im_str!("Sigma"))
+                            .range(0.001..=1.0)
+                            .speed(0.0005)
+                            .display_format(im_str!("%.4f"))
+                            .flags(SliderFlags::LOGARITHMIC)
+                            .build(ui, sigma);
+                        Drag::new(im_str!("Delta Time"))
+                            .range(0.001..=1.0)
+                            .speed(0.001)
+                            .flags(SliderFlags::LOGARITHMIC)
+                            .build(ui, delta_time);
+                    }
+                }
+                ui.separator();
+
+                if ui.small_button(im_str!("Glider Gun")) {
+                    *init = Pattern::parse_rle(GLIDER_GUN).map(Init::Pattern);
+                }
+                ui.same_line(0.0);
+                if ui.small_button(im_str!("Random")) {
+                    *seed = seed.wrapping_add(1);
+                    *init = Some(Init::Soup(*seed));
+                }
+                ui.same_line(0.0);
+                if ui.small_button(im_str!("Clear")) {
+                    *init = Some(Init::Clear);
+                }
+                ui.input_text(im_str!("Path"), path).build();
+                ui.same_line(0.0);
+                load = ui.small_button(im_str!("Load##pattern"));
+                if let Some(err) = path_error {
+                    ui.text(err);
+                }
+                ui.separator();
+                lut.render_ui(ui);
+            });
+
+        if let Some(rule) = rule {
+            self.set_rule(rule);
+        }
+
+        if load {
+            self.load_pattern();
+        }
+
+        let post = &mut self.post;
+
+        Window::new(im_str!("Post Processing"))
+            .always_auto_resize(true)
+            .build(ui, || post.render_ui(ui));
+    }
+}
+
+impl PointerInput for Automaton {}
+
+impl ActionInput for Automaton {
+    fn actions(&self, actions: &mut Vec<Action>) {
+        actions.extend_from_slice(&[RESET, PAUSE, STEP]);
+    }
+
+    // reset starts a new random soup, like the Random button
+    fn action(&mut self, name: &str) {
+        if name == RESET.name {
+            self.seed = self.seed.wrapping_add(1);
+            self.init = Some(Init::Soup(self.seed));
+        } else if name == PAUSE.name {
+            self.running = !self.running;
+        } else if name == STEP.name {
+            self.step = true;
+        }
+    }
+}
+
+impl App for Automaton {
+    fn save_preset(&self, name: &str) -> Result<(), serialize::Error> {
+        serialize::serialize(name, &self.preset())
+    }
+
+    fn load_preset(&mut self, name: &str) -> Result<(), serialize::Error> {
+        self.set_preset(serialize::deserialize(name)?);
+        Ok(())
+    }
+}
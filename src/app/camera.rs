@@ -0,0 +1,142 @@
+use crevice::std430::{AsStd430, Vec2};
+use imgui::{im_str, Drag, SliderFlags, Ui};
+
+use crate::input::{Button, Pointer};
+use crate::util::TextureDesc;
+
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 256.0;
+const ZOOM_PER_LINE: f32 = 1.2;
+
+// fragment shaders sample the texture at uv * scale + offset, where uv is from fullscreen.vert
+// and anything outside of 0 to 1 is drawn as background
+#[derive(AsStd430, Debug)]
+pub struct CameraConstants {
+    pub scale: Vec2,
+    pub offset: Vec2,
+}
+
+// 2d view of a simulation texture: scroll to zoom around the cursor, drag with right or middle to pan
+#[derive(Clone, Debug)]
+pub struct Camera {
+    pub center: [f32; 2], // in texture uv
+    pub zoom: f32,        // 1 shows the whole texture
+    pub letterbox: bool,  // keep the texture's aspect ratio instead of stretching it
+    pub filter: bool,     // linear instead of nearest sampling, index into the app's pipelines
+    texture_aspect: f32,
+    window_aspect: f32,
+}
+
+impl Camera {
+    pub fn new(desc: &TextureDesc) -> Self {
+        let texture_aspect = desc.width as f32 / desc.height as f32;
+
+        Self {
+            center: [0.5; 2],
+            zoom: 1.0,
+            letterbox: false,
+            filter: false,
+            texture_aspect,
+            window_aspect: texture_aspect,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.center = [0.5; 2];
+        self.zoom = 1.0;
+    }
+
+    // size of the visible area in texture uv
+    fn scale(&self) -> [f32; 2] {
+        let mut scale = [1.0 / self.zoom; 2];
+
+        if self.letterbox {
+            let ratio = self.window_aspect / self.texture_aspect;
+
+            if ratio > 1.0 {
+                scale[0] *= ratio;
+            } else {
+                scale[1] /= ratio;
+            }
+        }
+
+        scale
+    }
+
+    // window uv (see Pointer::uv) to texture uv
+    pub fn to_texture(&self, [u, v]: [f32; 2]) -> [f32; 2] {
+        let [sx, sy] = self.scale();
+        let [cx, cy] = self.center;
+
+        [(u - 0.5) * sx + cx, (v - 0.5) * sy + cy]
+    }
+
+    pub fn constants(&self) -> CameraConstants {
+        let [sx, sy] = self.scale();
+        let [cx, cy] = self.center;
+
+        CameraConstants {
+            scale: Vec2 { x: sx, y: sy },
+            offset: Vec2 {
+                x: cx - sx * 0.5,
+                y: cy - sy * 0.5,
+            },
+        }
+    }
+
+    // the texel under the cursor, None if the cursor is outside of the texture
+    pub fn texel(&self, pointer: &Pointer, width: u32, height: u32) -> Option<[f32; 2]> {
+        let [u, v] = self.to_texture(pointer.uv()?);
+
+        if (0.0..1.0).contains(&u) && (0.0..1.0).contains(&v) {
+            Some([u * width as f32, v * height as f32])
+        } else {
+            None
+        }
+    }
+
+    pub fn pointer(&mut self, pointer: &Pointer) {
+        let [width, height] = pointer.window_size();
+        if width > 0.0 && height > 0.0 {
+            self.window_aspect = width / height;
+        }
+
+        let scroll = pointer.scroll();
+        if scroll != 0.0 {
+            // keep the point under the cursor in place
+            let anchor = pointer.uv().unwrap_or([0.5; 2]);
+            let before = self.to_texture(anchor);
+
+            self.zoom = (self.zoom * ZOOM_PER_LINE.powf(scroll)).clamp(MIN_ZOOM, MAX_ZOOM);
+
+            let after = self.to_texture(anchor);
+            self.center[0] += before[0] - after[0];
+            self.center[1] += before[1] - after[1];
+        }
+
+        let drag = pointer
+            .drag(Button::Right)
+            .or_else(|| pointer.drag(Button::Middle));
+
+        if let Some([du, dv]) = drag {
+            let [sx, sy] = self.scale();
+            self.center[0] -= du * sx;
+            self.center[1] -= dv * sy;
+        }
+    }
+
+    pub fn render_ui(&mut self, ui: &Ui<'_>) {
+        Drag::new(im_str!("Zoom"))
+            .range(MIN_ZOOM..=MAX_ZOOM)
+            .speed(0.01)
+            .flags(SliderFlags::LOGARITHMIC)
+            .build(ui, &mut self.zoom);
+        ui.checkbox(im_str!("Letterbox"), &mut self.letterbox);
+        ui.same_line(0.0);
+        ui.checkbox(im_str!("Linear Filter"), &mut self.filter);
+        ui.same_line(0.0);
+        if ui.small_button(im_str!("Reset View")) {
+            self.reset();
+        }
+    }
+}
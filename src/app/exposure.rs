@@ -0,0 +1,128 @@
+use ::wgpu::{Buffer, BufferBindingType, BufferUsage, ComputePass};
+use crevice::std430::{AsStd430, Std430};
+use imgui::{im_str, Drag, SliderFlags, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::util::InitType;
+use crate::wgpu::{
+    BindGroupEntry, BufferDesc, ComputePipelineDesc, FullComputePipeline, PipelineExt,
+    ReduceSource, Reduction, TextureResult, WgpuBase,
+};
+
+// see exposure_adapt.comp
+pub const MODE_MANUAL: u32 = 0;
+pub const MODE_AVERAGE: u32 = 1;
+pub const MODE_MAX: u32 = 2;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExposureConfig {
+    pub mode: u32,
+    pub key: f32,
+    pub rate: f32,
+}
+
+impl Default for ExposureConfig {
+    fn default() -> Self {
+        Self {
+            mode: MODE_MANUAL,
+            key: 0.25,
+            rate: 0.05,
+        }
+    }
+}
+
+#[derive(AsStd430, Debug)]
+struct AdaptConstants {
+    mode: u32,
+    key: f32,
+    rate: f32,
+}
+
+// computes an exposure scale from the mean or max of a float texture, entirely on the gpu
+pub struct AutoExposure {
+    pub config: ExposureConfig,
+    pub buffer: Buffer, // std430 { float average; float maximum; float scale; }
+    reduction: Reduction,
+    adapt_pipeline: FullComputePipeline,
+}
+
+impl AutoExposure {
+    pub fn new(wgpu_base: &mut WgpuBase, texture: &TextureResult) -> Self {
+        let reduction = wgpu_base.reduction(ReduceSource::Texture {
+            texture,
+            channel: 0,
+        });
+
+        let buffer = wgpu_base.buffer(
+            BufferDesc {
+                size: std::mem::size_of::<[f32; 3]>(),
+                usage: BufferUsage::STORAGE,
+            },
+            InitType::Data(bytemuck::cast_slice(&[0.0f32, 0.0, 1.0])),
+        );
+
+        let adapt_pipeline = wgpu_base.compute_pipeline(ComputePipelineDesc {
+            bind_groups: vec![wgpu_base.bind_group(&[
+                BindGroupEntry::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    buffer: &reduction.output,
+                },
+                BindGroupEntry::Buffer {
+                    ty: BufferBindingType::Storage { read_only: false },
+                    buffer: &buffer,
+                },
+            ])],
+            shader: "exposure_adapt.comp",
+            defines: &[],
+            push_constants: Some(AdaptConstants::std430_size_static() as _),
+        });
+
+        Self {
+            config: Default::default(),
+            buffer,
+            reduction,
+            adapt_pipeline,
+        }
+    }
+
+    pub fn encode<'a>(&'a self, compute_pass: &mut ComputePass<'a>) {
+        let ExposureConfig { mode, key, rate } = self.config;
+
+        if mode != MODE_MANUAL {
+            self.reduction.encode(compute_pass);
+        }
+
+        let constants = AdaptConstants { mode, key, rate };
+
+        compute_pass.begin(&self.adapt_pipeline);
+        compute_pass.pushc(constants.as_std430().as_bytes());
+        compute_pass.dispatch(1, 1, 1);
+    }
+
+    pub fn render_ui(&mut self, ui: &Ui<'_>) {
+        let ExposureConfig { mode, key, rate } = &mut self.config;
+
+        ui.text(im_str!("Auto Exposure"));
+        ui.radio_button(im_str!("Manual"), mode, MODE_MANUAL);
+        ui.same_line(0.0);
+        ui.radio_button(im_str!("Average"), mode, MODE_AVERAGE);
+        ui.same_line(0.0);
+        ui.radio_button(im_str!("Max"), mode, MODE_MAX);
+
+        if *mode == MODE_AVERAGE {
+            Drag::new(im_str!("Key"))
+                .range(0.001..=1.0)
+                .speed(0.005)
+                .flags(SliderFlags::LOGARITHMIC)
+                .build(ui, key);
+        }
+
+        if *mode != MODE_MANUAL {
+            Drag::new(im_str!("Adapt Rate"))
+                .range(0.0..=1.0)
+                .speed(0.005)
+                .flags(SliderFlags::LOGARITHMIC)
+                .build(ui, rate);
+        }
+    }
+}
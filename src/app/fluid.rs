@@ -0,0 +1,492 @@
+use ::wgpu::{
+    CommandEncoder, ComputePass, RenderPass, StorageTextureAccess, TextureFormat, TextureUsage,
+};
+use crevice::std430::{AsStd430, Std430, Vec2, Vec4};
+use imgui::{im_str, ColorEdit, ColorEditFlags, Drag, SliderFlags, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::imgui::ImguiWgpuRender;
+use crate::input::{Button, Pointer, PointerInput};
+use crate::keymap::{Action, ActionInput};
+use crate::serialize;
+use crate::util::{srgb_to_linear, texture_size, CreateFromWgpu, InitType, TextureDesc};
+use crate::wgpu::{
+    begin_render_pass, BindGroupEntry, ColorAttachment, ComputePipelineDesc, DispatchExt,
+    FullComputePipeline, FullRenderPipeline, PipelineExt, RenderPipelineDesc, TextureResult,
+    WgpuBase, WgpuWindowed, WgpuWindowedRender,
+};
+
+use super::post::{self, EffectSlot, PostStack};
+use super::registry::{App, PAUSE, RESET};
+
+// format is replaced per field, velocity uses xy of rgba since rg32float can't be used by advect.comp
+const DESC: TextureDesc = TextureDesc {
+    format: TextureFormat::Rgba32Float,
+    width: 1920 / 4,
+    height: 1015 / 4,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct FluidConfig {
+    delta_time: f32,
+    vorticity: f32,
+    pressure_iterations: u32, // rounded up to even, see Fluid::encode_sim
+    velocity_dissipation: f32,
+    dye_dissipation: f32,
+    splat_radius: f32,
+    force: f32,
+    dye_color: [f32; 3],
+}
+
+impl Default for FluidConfig {
+    fn default() -> Self {
+        Self {
+            delta_time: 1.0,
+            vorticity: 0.3,
+            pressure_iterations: 20,
+            velocity_dissipation: 0.01,
+            dye_dissipation: 0.005,
+            splat_radius: 6.0,
+            force: 1.0,
+            dye_color: [1.0, 0.3, 0.1],
+        }
+    }
+}
+
+#[derive(AsStd430, Debug)]
+struct AdvectConstants {
+    delta_time: f32,
+    dissipation: f32,
+}
+
+#[derive(AsStd430, Debug)]
+struct SplatConstants {
+    value: Vec4,
+    pos: Vec2,
+    radius: f32,
+}
+
+#[derive(AsStd430, Debug)]
+struct VorticityConstants {
+    strength: f32,
+    delta_time: f32,
+}
+
+// everything that can be tweaked from imgui, saved and loaded with serialize.rs
+#[derive(Serialize, Deserialize, Debug)]
+struct Preset {
+    config: FluidConfig,
+    post: Vec<EffectSlot>,
+}
+
+// mouse drag of the current frame, in texels
+#[derive(Clone, Copy)]
+struct Splat {
+    pos: [f32; 2],
+    delta: [f32; 2],
+}
+
+fn read(texture: &TextureResult) -> BindGroupEntry<'_> {
+    BindGroupEntry::Texture {
+        storage: Some(StorageTextureAccess::ReadOnly),
+        desc: texture.desc.clone(),
+        view: &texture.view,
+    }
+}
+
+fn write(texture: &TextureResult) -> BindGroupEntry<'_> {
+    BindGroupEntry::Texture {
+        storage: Some(StorageTextureAccess::WriteOnly),
+        desc: texture.desc.clone(),
+        view: &texture.view,
+    }
+}
+
+// every field is written an even number of times per frame, so that the latest state is always in [0]
+// and each pass can have a single pipeline with fixed bindings
+pub struct Fluid {
+    velocity: [TextureResult; 2],
+    dye: [TextureResult; 2],
+    pressure: [TextureResult; 2],
+    curl_pipeline: FullComputePipeline,
+    vorticity_pipeline: FullComputePipeline,
+    divergence_pipeline: FullComputePipeline,
+    jacobi_pipelines: [FullComputePipeline; 2], // indexed by which pressure texture is read
+    gradient_pipeline: FullComputePipeline,
+    advect_velocity_pipeline: FullComputePipeline,
+    splat_velocity_pipeline: FullComputePipeline,
+    advect_dye_pipeline: FullComputePipeline,
+    splat_dye_pipeline: FullComputePipeline,
+    render_pipeline: FullRenderPipeline,
+    config: FluidConfig,
+    splat: Option<Splat>,
+    running: bool,
+    reset: bool,
+    post: PostStack,
+}
+
+impl CreateFromWgpu for Fluid {
+    fn new(wgpu_base: &mut WgpuBase, swapchain_desc: &TextureDesc) -> Self {
+        let usage = TextureUsage::COPY_DST | TextureUsage::SAMPLED | TextureUsage::STORAGE;
+
+        let field = |format| {
+            let desc = TextureDesc {
+                format,
+                ..DESC.clone()
+            }
+            .into_2d(usage);
+
+            wgpu_base.texture(&desc, InitType::Zeros)
+        };
+
+        let velocity = [
+            field(TextureFormat::Rgba32Float),
+            field(TextureFormat::Rgba32Float),
+        ];
+        let dye = [
+            field(TextureFormat::Rgba32Float),
+            field(TextureFormat::Rgba32Float),
+        ];
+        let pressure = [
+            field(TextureFormat::R32Float),
+            field(TextureFormat::R32Float),
+        ];
+        let divergence = field(TextureFormat::R32Float);
+        let curl = field(TextureFormat::R32Float);
+
+        let mut pipeline = |shader, entries: &[BindGroupEntry<'_>], push_constants| {
+            wgpu_base.compute_pipeline(ComputePipelineDesc {
+                bind_groups: vec![wgpu_base.bind_group(entries)],
+                shader,
+                defines: &[],
+                push_constants,
+            })
+        };
+
+        let advect = Some(AdvectConstants::std430_size_static() as _);
+        let splat = Some(SplatConstants::std430_size_static() as _);
+
+        let curl_pipeline = pipeline("fluid/curl.comp", &[read(&velocity[0]), write(&curl)], None);
+        let vorticity_pipeline = pipeline(
+            "fluid/vorticity.comp",
+            &[read(&velocity[0]), read(&curl), write(&velocity[1])],
+            Some(VorticityConstants::std430_size_static() as _),
+        );
+        let divergence_pipeline = pipeline(
+            "fluid/divergence.comp",
+            &[read(&velocity[1]), write(&divergence)],
+            None,
+        );
+        let jacobi_pipelines = [
+            pipeline(
+                "fluid/jacobi.comp",
+                &[read(&pressure[0]), read(&divergence), write(&pressure[1])],
+                None,
+            ),
+            pipeline(
+                "fluid/jacobi.comp",
+                &[read(&pressure[1]), read(&divergence), write(&pressure[0])],
+                None,
+            ),
+        ];
+        let gradient_pipeline = pipeline(
+            "fluid/gradient.comp",
+            &[read(&pressure[0]), read(&velocity[1]), write(&velocity[0])],
+            None,
+        );
+        let advect_velocity_pipeline = pipeline(
+            "fluid/advect.comp",
+            &[read(&velocity[0]), read(&velocity[0]), write(&velocity[1])],
+            advect,
+        );
+        let splat_velocity_pipeline = pipeline(
+            "fluid/splat.comp",
+            &[read(&velocity[1]), write(&velocity[0])],
+            splat,
+        );
+        let advect_dye_pipeline = pipeline(
+            "fluid/advect.comp",
+            &[read(&velocity[0]), read(&dye[0]), write(&dye[1])],
+            advect,
+        );
+        let splat_dye_pipeline =
+            pipeline("fluid/splat.comp", &[read(&dye[1]), write(&dye[0])], splat);
+
+        let render_pipeline = wgpu_base.render_pipeline(RenderPipelineDesc {
+            bind_groups: vec![wgpu_base.bind_group(&[BindGroupEntry::Texture {
+                storage: None,
+                desc: dye[0].desc.clone(),
+                view: &dye[0].view,
+            }])],
+            shader: "fluid/render.frag",
+            targets: vec![post::FORMAT.into()],
+            push_constants: None,
+        });
+
+        let post = PostStack::new(wgpu_base, &DESC, swapchain_desc.format);
+
+        Self {
+            velocity,
+            dye,
+            pressure,
+            curl_pipeline,
+            vorticity_pipeline,
+            divergence_pipeline,
+            jacobi_pipelines,
+            gradient_pipeline,
+            advect_velocity_pipeline,
+            splat_velocity_pipeline,
+            advect_dye_pipeline,
+            splat_dye_pipeline,
+            render_pipeline,
+            config: Default::default(),
+            splat: None,
+            running: true,
+            reset: false,
+            post,
+        }
+    }
+}
+
+impl Fluid {
+    fn preset(&self) -> Preset {
+        Preset {
+            config: self.config.clone(),
+            post: self.post.effects.clone(),
+        }
+    }
+
+    fn set_preset(&mut self, preset: Preset) {
+        self.config = preset.config;
+        self.post.effects = preset.post;
+    }
+
+    fn encode_sim<'a>(&'a self, compute_pass: &mut ComputePass<'a>) {
+        let FluidConfig {
+            delta_time,
+            vorticity,
+            pressure_iterations,
+            velocity_dissipation,
+            dye_dissipation,
+            splat_radius,
+            force,
+            dye_color,
+        } = self.config;
+        let [r, g, b] = srgb_to_linear(dye_color);
+
+        let mut dispatch = |pipeline, constants: Option<&[u8]>| {
+            compute_pass.begin(pipeline);
+            if let Some(constants) = constants {
+                compute_pass.pushc(constants);
+            }
+            compute_pass.dispatch_for_texture(pipeline, &DESC);
+        };
+
+        let vorticity = VorticityConstants {
+            strength: vorticity,
+            delta_time,
+        };
+
+        dispatch(&self.curl_pipeline, None);
+        dispatch(
+            &self.vorticity_pipeline,
+            Some(vorticity.as_std430().as_bytes()),
+        );
+        dispatch(&self.divergence_pipeline, None);
+
+        // the previous pressure is the initial guess, an even number of iterations ends in pressure[0]
+        for _ in 0..(pressure_iterations + 1) / 2 {
+            dispatch(&self.jacobi_pipelines[0], None);
+            dispatch(&self.jacobi_pipelines[1], None);
+        }
+
+        dispatch(&self.gradient_pipeline, None);
+
+        let advect = |dissipation| AdvectConstants {
+            delta_time,
+            dissipation,
+        };
+
+        // radius of 0 still has to run, to bring the field back into [0]
+        let (pos, radius, [dx, dy]) = match self.splat {
+            Some(Splat { pos, delta }) => (pos, splat_radius, delta),
+            None => ([0.0; 2], 0.0, [0.0; 2]),
+        };
+        let splat = |value: [f32; 4]| {
+            let [x, y, z, w] = value;
+
+            SplatConstants {
+                value: Vec4 { x, y, z, w },
+                pos: Vec2 {
+                    x: pos[0],
+                    y: pos[1],
+                },
+                radius,
+            }
+        };
+
+        dispatch(
+            &self.advect_velocity_pipeline,
+            Some(advect(velocity_dissipation).as_std430().as_bytes()),
+        );
+        dispatch(
+            &self.splat_velocity_pipeline,
+            Some(
+                splat([dx * force, dy * force, 0.0, 0.0])
+                    .as_std430()
+                    .as_bytes(),
+            ),
+        );
+        dispatch(
+            &self.advect_dye_pipeline,
+            Some(advect(dye_dissipation).as_std430().as_bytes()),
+        );
+        dispatch(
+            &self.splat_dye_pipeline,
+            Some(splat([r, g, b, 1.0]).as_std430().as_bytes()),
+        );
+    }
+}
+
+impl WgpuWindowedRender for Fluid {
+    fn render<'a>(&'a mut self, _: &WgpuWindowed<'_>, render_pass: &mut RenderPass<'a>) {
+        self.post.render(render_pass);
+    }
+
+    fn render_encoder(
+        &mut self,
+        wgpu_windowed: &WgpuWindowed<'_>,
+        encoder: &mut CommandEncoder,
+        after: bool,
+    ) {
+        if after {
+            return;
+        }
+
+        let base = &wgpu_windowed.base;
+
+        if self.reset {
+            self.reset = false;
+
+            for texture in &[&self.velocity[0], &self.dye[0], &self.pressure[0]] {
+                base.write_texture(texture, &vec![0; texture_size(&texture.desc)]);
+            }
+        }
+
+        if self.running {
+            let mut compute_pass = encoder.begin_compute_pass(&Default::default());
+            self.encode_sim(&mut compute_pass);
+        }
+
+        {
+            let mut render_pass =
+                begin_render_pass(encoder, &[ColorAttachment::new(self.post.input())]);
+            render_pass.begin(&self.render_pipeline);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        self.post.encode(encoder);
+    }
+}
+
+impl PointerInput for Fluid {
+    fn pointer(&mut self, pointer: &Pointer) {
+        self.splat = if pointer.down(Button::Left) {
+            pointer.texel(DESC.width, DESC.height).map(|pos| Splat {
+                pos,
+                delta: pointer.texel_delta(DESC.width, DESC.height),
+            })
+        } else {
+            None
+        };
+    }
+}
+
+impl ImguiWgpuRender for Fluid {
+    fn render_ui(&mut self, ui: &mut imgui::Ui<'_>) {
+        let FluidConfig {
+            delta_time,
+            vorticity,
+            pressure_iterations,
+            velocity_dissipation,
+            dye_dissipation,
+            splat_radius,
+            force,
+            dye_color,
+        } = &mut self.config;
+        let running = &mut self.running;
+        let reset = &mut self.reset;
+
+        Window::new(im_str!("Fluid"))
+            .always_auto_resize(true)
+            .build(ui, || {
+                ui.checkbox(im_str!("Running"), running);
+                ui.same_line(0.0);
+                *reset |= ui.small_button(im_str!("Reset"));
+                Drag::new(im_str!("Delta Time"))
+                    .range(0.01..=4.0)
+                    .speed(0.005)
+                    .build(ui, delta_time);
+                Drag::new(im_str!("Vorticity"))
+                    .range(0.0..=4.0)
+                    .speed(0.005)
+                    .build(ui, vorticity);
+                Drag::new(im_str!("Pressure Iterations"))
+                    .range(2..=100)
+                    .build(ui, pressure_iterations);
+                Drag::new(im_str!("Velocity Dissipation"))
+                    .range(0.0..=1.0)
+                    .speed(0.001)
+                    .flags(SliderFlags::LOGARITHMIC)
+                    .build(ui, velocity_dissipation);
+                Drag::new(im_str!("Dye Dissipation"))
+                    .range(0.0..=1.0)
+                    .speed(0.001)
+                    .flags(SliderFlags::LOGARITHMIC)
+                    .build(ui, dye_dissipation);
+                ui.separator();
+                Drag::new(im_str!("Splat Radius"))
+                    .range(1.0..=64.0)
+                    .speed(0.1)
+                    .build(ui, splat_radius);
+                Drag::new(im_str!("Force"))
+                    .range(0.0..=16.0)
+                    .speed(0.01)
+                    .build(ui, force);
+                ColorEdit::new(im_str!("Dye"), dye_color)
+                    .flags(ColorEditFlags::FLOAT | ColorEditFlags::HDR)
+                    .build(ui);
+            });
+
+        let post = &mut self.post;
+
+        Window::new(im_str!("Post Processing"))
+            .always_auto_resize(true)
+            .build(ui, || post.render_ui(ui));
+    }
+}
+
+impl ActionInput for Fluid {
+    fn actions(&self, actions: &mut Vec<Action>) {
+        actions.extend_from_slice(&[RESET, PAUSE]);
+    }
+
+    fn action(&mut self, name: &str) {
+        if name == RESET.name {
+            self.reset = true;
+        } else if name == PAUSE.name {
+            self.running = !self.running;
+        }
+    }
+}
+
+impl App for Fluid {
+    fn save_preset(&self, name: &str) -> Result<(), serialize::Error> {
+        serialize::serialize(name, &self.preset())
+    }
+
+    fn load_preset(&mut self, name: &str) -> Result<(), serialize::Error> {
+        self.set_preset(serialize::deserialize(name)?);
+        Ok(())
+    }
+}
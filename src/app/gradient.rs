@@ -0,0 +1,388 @@
+use ::wgpu::{TextureFormat, TextureUsage};
+use imgui::{im_str, ColorEdit, ColorEditFlags, ComboBox, Drag, ImStr, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::util::{InitType, TextureDesc};
+use crate::wgpu::{TextureResult, WgpuBase};
+
+// colors are edited and stored as srgb, the srgb format converts them to linear when sampled
+pub const LUT_DESC: TextureDesc = TextureDesc {
+    format: TextureFormat::Rgba8UnormSrgb,
+    width: 256,
+    height: 1,
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Colormap {
+    Custom,
+    Viridis,
+    Magma,
+    Inferno,
+    Turbo,
+    Cubehelix,
+}
+
+impl Colormap {
+    const ALL: [Self; 6] = [
+        Self::Custom,
+        Self::Viridis,
+        Self::Magma,
+        Self::Inferno,
+        Self::Turbo,
+        Self::Cubehelix,
+    ];
+
+    fn name(self) -> &'static ImStr {
+        match self {
+            Self::Custom => im_str!("Custom"),
+            Self::Viridis => im_str!("Viridis"),
+            Self::Magma => im_str!("Magma"),
+            Self::Inferno => im_str!("Inferno"),
+            Self::Turbo => im_str!("Turbo"),
+            Self::Cubehelix => im_str!("Cubehelix"),
+        }
+    }
+
+    // None for Custom, which uses the gradient stops instead
+    fn eval(self, x: f32) -> Option<[f32; 3]> {
+        let x = x.max(0.0).min(1.0);
+
+        // https://www.shadertoy.com/view/WlfXRN (CC0)
+        const VIRIDIS: [[f32; 3]; 7] = [
+            [0.277_727_33, 0.005_407_344_5, 0.334_099_8],
+            [0.105_093_04, 1.404_613_5, 1.384_590_2],
+            [-0.330_861_83, 0.214_847_56, 0.095_095_16],
+            [-4.634_230_5, -5.799_101, -19.332_441],
+            [6.228_27, 14.179_933, 56.690_553],
+            [4.776_385, -13.745_145, -65.353_035],
+            [-5.435_456, 4.645_852_6, 26.312_435],
+        ];
+        const MAGMA: [[f32; 3]; 7] = [
+            [-0.002_136_485, -0.000_749_655_05, -0.005_386_128],
+            [0.251_660_54, 0.677_523_24, 2.494_026_6],
+            [8.353_717, -3.577_719_4, 0.314_467_9],
+            [-27.668_733, 14.264_731, -13.649_213],
+            [52.176_14, -27.943_607, 12.944_169],
+            [-50.768_524, 29.046_583, 4.234_153],
+            [18.655_705, -11.489_773, -5.601_961_5],
+        ];
+        const INFERNO: [[f32; 3]; 7] = [
+            [0.000_218_940_37, 0.001_651_004_6, -0.019_480_899],
+            [0.106_513_42, 0.563_956_4, 3.932_712_3],
+            [11.602_493, -3.972_854, -15.942_394],
+            [-41.703_995, 17.436_398, 44.354_145],
+            [77.162_94, -33.402_36, -81.807_31],
+            [-71.319_43, 32.626_064, 73.209_52],
+            [25.131_126, -12.242_669, -23.070_326],
+        ];
+
+        let poly = |coeffs: &[[f32; 3]]| {
+            let mut color = [0.0; 3];
+            for coeff in coeffs.iter().rev() {
+                for channel in 0..3 {
+                    color[channel] = color[channel] * x + coeff[channel];
+                }
+            }
+            color
+        };
+
+        let color = match self {
+            Self::Custom => return None,
+            Self::Viridis => poly(&VIRIDIS),
+            Self::Magma => poly(&MAGMA),
+            Self::Inferno => poly(&INFERNO),
+            Self::Turbo => {
+                // https://gist.github.com/mikhailov-work/0d177465a8151eb6ede1768d51d476c7 (Apache-2.0)
+                let red = [
+                    0.135_721_38,
+                    4.615_392_6,
+                    -42.660_324,
+                    132.131_08,
+                    -152.942_4,
+                    59.286_38,
+                ];
+                let green = [
+                    0.091_402_61,
+                    2.194_188_4,
+                    4.842_966_6,
+                    -14.185_033,
+                    4.277_298_7,
+                    2.829_566,
+                ];
+                let blue = [
+                    0.106_673_3,
+                    12.641_946,
+                    -60.582_05,
+                    110.362_77,
+                    -89.903_11,
+                    27.348_25,
+                ];
+
+                let eval = |c: [f32; 6]| -> f32 {
+                    c.iter()
+                        .enumerate()
+                        .map(|(power, c)| c * x.powi(power as _))
+                        .sum()
+                };
+
+                [eval(red), eval(green), eval(blue)]
+            }
+            Self::Cubehelix => {
+                // Green 2011, with start = 0.5, rotations = -1.5, hue = 1
+                let angle = std::f32::consts::TAU * (0.5 / 3.0 + 1.0 - 1.5 * x);
+                let amp = x * (1.0 - x) / 2.0;
+                let (sin, cos) = angle.sin_cos();
+
+                [
+                    x + amp * (-0.148_61 * cos + 1.782_77 * sin),
+                    x + amp * (-0.292_27 * cos - 0.906_49 * sin),
+                    x + amp * (1.972_94 * cos),
+                ]
+            }
+        };
+
+        Some(color)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Stop {
+    pub pos: f32,
+    pub color: [f32; 3],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Gradient {
+    pub colormap: Colormap,
+    pub stops: Vec<Stop>, // only used for Colormap::Custom, can be unsorted
+}
+
+impl Gradient {
+    pub fn two_color(background: [f32; 3], foreground: [f32; 3]) -> Self {
+        Self {
+            colormap: Colormap::Custom,
+            stops: vec![
+                Stop {
+                    pos: 0.0,
+                    color: background,
+                },
+                Stop {
+                    pos: 1.0,
+                    color: foreground,
+                },
+            ],
+        }
+    }
+
+    fn sorted_stops(&self) -> Vec<Stop> {
+        // a preset can contain anything, a nan position would make the sort panic
+        let mut stops: Vec<Stop> = self
+            .stops
+            .iter()
+            .copied()
+            .filter(|stop| stop.pos.is_finite())
+            .collect();
+        stops.sort_by(|a, b| a.pos.partial_cmp(&b.pos).unwrap());
+        stops
+    }
+
+    fn eval_stops(stops: &[Stop], x: f32) -> [f32; 3] {
+        let (first, last) = match (stops.first(), stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return [0.0; 3],
+        };
+
+        if x <= first.pos {
+            return first.color;
+        }
+
+        for pair in stops.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+
+            if x <= b.pos {
+                let t = if b.pos > a.pos {
+                    (x - a.pos) / (b.pos - a.pos)
+                } else {
+                    0.0
+                };
+
+                let mut color = [0.0; 3];
+                for channel in 0..3 {
+                    color[channel] = a.color[channel] + (b.color[channel] - a.color[channel]) * t;
+                }
+                return color;
+            }
+        }
+
+        last.color
+    }
+
+    pub fn eval(&self, x: f32) -> [f32; 3] {
+        self.colormap
+            .eval(x)
+            .unwrap_or_else(|| Self::eval_stops(&self.sorted_stops(), x))
+    }
+
+    // srgb encoded rgba8 texels of LUT_DESC
+    pub fn lut(&self) -> Vec<u8> {
+        let stops = self.sorted_stops();
+        let len = LUT_DESC.width;
+
+        (0..len)
+            .flat_map(|index| {
+                let x = index as f32 / (len - 1) as f32;
+                let color = self
+                    .colormap
+                    .eval(x)
+                    .unwrap_or_else(|| Self::eval_stops(&stops, x));
+
+                let [r, g, b] = color;
+                let to_u8 = |c: f32| (c.max(0.0).min(1.0) * 255.0).round() as u8;
+
+                vec![to_u8(r), to_u8(g), to_u8(b), 255]
+            })
+            .collect()
+    }
+
+    // returns true if anything changed
+    pub fn render_ui(&mut self, ui: &Ui<'_>) -> bool {
+        let mut changed = false;
+
+        let names: Vec<&ImStr> = Colormap::ALL.iter().map(|map| map.name()).collect();
+        let mut current = Colormap::ALL
+            .iter()
+            .position(|map| *map == self.colormap)
+            .unwrap();
+
+        if ComboBox::new(im_str!("Colormap")).build_simple_string(ui, &mut current, &names) {
+            self.colormap = Colormap::ALL[current];
+            changed = true;
+        }
+
+        if self.colormap != Colormap::Custom {
+            // copy a builtin colormap into stops so that it can be tweaked
+            if ui.small_button(im_str!("Edit as Custom")) {
+                self.stops = (0..=8)
+                    .map(|index| {
+                        let pos = index as f32 / 8.0;
+                        Stop {
+                            pos,
+                            color: self.eval(pos),
+                        }
+                    })
+                    .collect();
+                self.colormap = Colormap::Custom;
+                changed = true;
+            }
+
+            self.preview(ui);
+            return changed;
+        }
+
+        let mut remove = None;
+
+        for (index, stop) in self.stops.iter_mut().enumerate() {
+            let id = ui.push_id(index as i32);
+
+            changed |= ColorEdit::new(im_str!("##color"), &mut stop.color)
+                .flags(ColorEditFlags::NO_INPUTS)
+                .build(ui);
+            ui.same_line(0.0);
+            changed |= Drag::new(im_str!("##pos"))
+                .range(0.0..=1.0)
+                .speed(0.005)
+                .display_format(im_str!("%.3f"))
+                .build(ui, &mut stop.pos);
+            ui.same_line(0.0);
+            if ui.small_button(im_str!("Remove")) {
+                remove = Some(index);
+            }
+
+            id.pop(ui);
+        }
+
+        if let Some(index) = remove {
+            self.stops.remove(index);
+            changed = true;
+        }
+
+        if ui.small_button(im_str!("Add Stop")) {
+            let pos = 0.5;
+            self.stops.push(Stop {
+                pos,
+                color: self.eval(pos),
+            });
+            changed = true;
+        }
+
+        self.preview(ui);
+        changed
+    }
+
+    fn preview(&self, ui: &Ui<'_>) {
+        const SEGMENTS: usize = 32;
+        let (width, height) = (256.0, 16.0);
+
+        let [x, y] = ui.cursor_screen_pos();
+        let draw_list = ui.get_window_draw_list();
+
+        for segment in 0..SEGMENTS {
+            let a = segment as f32 / SEGMENTS as f32;
+            let b = (segment + 1) as f32 / SEGMENTS as f32;
+            let [ar, ag, ab] = self.eval(a);
+            let [br, bg, bb] = self.eval(b);
+
+            draw_list.add_rect_filled_multicolor(
+                [x + a * width, y],
+                [x + b * width, y + height],
+                [ar, ag, ab, 1.0],
+                [br, bg, bb, 1.0],
+                [br, bg, bb, 1.0],
+                [ar, ag, ab, 1.0],
+            );
+        }
+
+        ui.dummy([width, height]);
+    }
+}
+
+// gradient uploaded as a 1d texture, sampled in shader.frag
+pub struct GradientLut {
+    pub texture: TextureResult,
+    gradient: Gradient,
+    dirty: bool,
+}
+
+impl GradientLut {
+    pub fn new(wgpu_base: &WgpuBase, gradient: Gradient) -> Self {
+        let desc = LUT_DESC.into_1d(TextureUsage::COPY_DST | TextureUsage::SAMPLED);
+        let texture = wgpu_base.texture(&desc, InitType::Data(&gradient.lut()));
+
+        Self {
+            texture,
+            gradient,
+            dirty: false,
+        }
+    }
+
+    pub fn gradient(&self) -> &Gradient {
+        &self.gradient
+    }
+
+    pub fn set(&mut self, gradient: Gradient) {
+        self.gradient = gradient;
+        self.dirty = true;
+    }
+
+    // upload the gradient if it was changed since the last call
+    pub fn update(&mut self, wgpu_base: &WgpuBase) {
+        if self.dirty {
+            self.dirty = false;
+            wgpu_base.write_texture(&self.texture, &self.gradient.lut());
+        }
+    }
+
+    pub fn render_ui(&mut self, ui: &Ui<'_>) {
+        self.dirty |= self.gradient.render_ui(ui);
+    }
+}
@@ -0,0 +1,422 @@
+use ::wgpu::{CommandEncoder, RenderPass, StorageTextureAccess, TextureFormat, TextureUsage};
+use crevice::std430::{AsStd430, Std430, Vec2};
+use imgui::{im_str, Drag, SliderFlags, Window};
+use serde::{Deserialize, Serialize};
+
+use crate::imgui::ImguiWgpuRender;
+use crate::input::{Button, Pointer, PointerInput};
+use crate::keymap::{Action, ActionInput};
+use crate::serialize;
+use crate::util::{CreateFromWgpu, InitType, SamplerDesc, TextureDesc};
+use crate::wgpu::{
+    begin_render_pass, BindGroupEntry, ColorAttachment, ComputePipelineDesc, DispatchExt,
+    FullComputePipeline, FullRenderPipeline, PipelineExt, RenderPipelineDesc, TextureResult,
+    WgpuBase, WgpuWindowed, WgpuWindowedRender,
+};
+
+use super::gradient::{Colormap, Gradient, GradientLut};
+use super::post::{self, EffectSlot, PostStack};
+use super::registry::{App, RESET};
+
+// x = chemical a, y = chemical b
+const DESC: TextureDesc = TextureDesc {
+    format: TextureFormat::Rg32Float,
+    width: 1920 / 2,
+    height: 1015 / 2,
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct Params {
+    feed: f32,
+    kill: f32,
+    diffuse_a: f32,
+    diffuse_b: f32,
+}
+
+impl Params {
+    // http://karlsims.com/rd.html
+    const PRESETS: [(&'static str, Self); 3] = [
+        (
+            "Coral",
+            Self {
+                feed: 0.0545,
+                kill: 0.062,
+                diffuse_a: 1.0,
+                diffuse_b: 0.5,
+            },
+        ),
+        (
+            "Mitosis",
+            Self {
+                feed: 0.0367,
+                kill: 0.0649,
+                diffuse_a: 1.0,
+                diffuse_b: 0.5,
+            },
+        ),
+        (
+            "Spots",
+            Self {
+                feed: 0.035,
+                kill: 0.065,
+                diffuse_a: 1.0,
+                diffuse_b: 0.5,
+            },
+        ),
+    ];
+}
+
+#[derive(AsStd430, Debug)]
+struct StepConstants {
+    feed: f32,
+    kill: f32,
+    diffuse_a: f32,
+    diffuse_b: f32,
+    delta_time: f32,
+    brush: Vec2,
+    brush_radius: f32,
+}
+
+#[derive(AsStd430, Debug)]
+struct RenderConstants {
+    scale: f32,
+}
+
+// everything that can be tweaked from imgui, saved and loaded with serialize.rs
+#[derive(Serialize, Deserialize, Debug)]
+struct Preset {
+    params: Params,
+    steps: u32,
+    scale: f32,
+    gradient: Gradient,
+    post: Vec<EffectSlot>,
+}
+
+// a = 1 everywhere, with a few squares of b near the center to start the reaction
+fn initial_state() -> Vec<u8> {
+    let (width, height) = (DESC.width as i32, DESC.height as i32);
+    let seeds = [(0, 0), (-40, -25), (35, 30), (50, -40), (-30, 45)];
+
+    let data: Vec<[f32; 2]> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let seeded = seeds.iter().any(|(dx, dy)| {
+                let (cx, cy) = (width / 2 + dx, height / 2 + dy);
+                (x - cx).abs() < 5 && (y - cy).abs() < 5
+            });
+
+            [1.0, if seeded { 1.0 } else { 0.0 }]
+        })
+        .collect();
+
+    bytemuck::cast_slice(&data).to_vec()
+}
+
+pub struct GrayScott {
+    textures: [TextureResult; 2],
+    step_pipelines: [FullComputePipeline; 2], // indexed by which texture is read
+    render_pipelines: [FullRenderPipeline; 2],
+    current: usize, // which texture holds the latest state
+    params: Params,
+    steps: u32, // per frame
+    scale: f32,
+    brush: Option<[f32; 2]>, // in texels, while the mouse is held
+    brush_radius: f32,
+    reset: bool,
+    lut: GradientLut,
+    post: PostStack,
+}
+
+impl CreateFromWgpu for GrayScott {
+    fn new(wgpu_base: &mut WgpuBase, swapchain_desc: &TextureDesc) -> Self {
+        let desc =
+            DESC.into_2d(TextureUsage::COPY_DST | TextureUsage::SAMPLED | TextureUsage::STORAGE);
+
+        let state = initial_state();
+        let textures = [
+            wgpu_base.texture(&desc, InitType::Data(&state)),
+            wgpu_base.texture(&desc, InitType::Data(&state)),
+        ];
+
+        let lut = GradientLut::new(
+            wgpu_base,
+            Gradient {
+                colormap: Colormap::Magma,
+                stops: Vec::new(),
+            },
+        );
+
+        let storage = |access, index: usize| BindGroupEntry::Texture {
+            storage: Some(access),
+            desc: desc.clone(),
+            view: &textures[index].view,
+        };
+
+        let mut step_pipeline = |source: usize| {
+            let bind_group = wgpu_base.bind_group(&[
+                storage(StorageTextureAccess::ReadOnly, source),
+                storage(StorageTextureAccess::WriteOnly, 1 - source),
+            ]);
+
+            wgpu_base.compute_pipeline(ComputePipelineDesc {
+                bind_groups: vec![bind_group],
+                shader: "gray_scott/step.comp",
+                defines: &[],
+                push_constants: Some(StepConstants::std430_size_static() as _),
+            })
+        };
+        let step_pipelines = [step_pipeline(0), step_pipeline(1)];
+
+        let mut render_pipeline = |source: usize| {
+            let bind_group = wgpu_base.bind_group(&[
+                BindGroupEntry::Texture {
+                    storage: None,
+                    desc: desc.clone(),
+                    view: &textures[source].view,
+                },
+                BindGroupEntry::Sampler {
+                    desc: SamplerDesc {
+                        filter: false,
+                        ..Default::default()
+                    },
+                },
+                BindGroupEntry::Texture {
+                    storage: None,
+                    desc: lut.texture.desc.clone(),
+                    view: &lut.texture.view,
+                },
+                BindGroupEntry::Sampler {
+                    desc: SamplerDesc {
+                        filter: true,
+                        ..Default::default()
+                    },
+                },
+            ]);
+
+            wgpu_base.render_pipeline(RenderPipelineDesc {
+                bind_groups: vec![bind_group],
+                shader: "gray_scott/render.frag",
+                targets: vec![post::FORMAT.into()],
+                push_constants: Some(RenderConstants::std430_size_static() as _),
+            })
+        };
+        let render_pipelines = [render_pipeline(0), render_pipeline(1)];
+
+        let post = PostStack::new(wgpu_base, &DESC, swapchain_desc.format);
+
+        let (_, params) = Params::PRESETS[0];
+
+        Self {
+            textures,
+            step_pipelines,
+            render_pipelines,
+            current: 0,
+            params,
+            steps: 8,
+            scale: 2.0,
+            brush: None,
+            brush_radius: 8.0,
+            reset: false,
+            lut,
+            post,
+        }
+    }
+}
+
+impl GrayScott {
+    fn preset(&self) -> Preset {
+        Preset {
+            params: self.params,
+            steps: self.steps,
+            scale: self.scale,
+            gradient: self.lut.gradient().clone(),
+            post: self.post.effects.clone(),
+        }
+    }
+
+    fn set_preset(&mut self, preset: Preset) {
+        self.params = preset.params;
+        self.steps = preset.steps;
+        self.scale = preset.scale;
+        self.lut.set(preset.gradient);
+        self.post.effects = preset.post;
+    }
+}
+
+impl PointerInput for GrayScott {
+    fn pointer(&mut self, pointer: &Pointer) {
+        self.brush = if pointer.down(Button::Left) {
+            pointer.texel(DESC.width, DESC.height)
+        } else {
+            None
+        };
+    }
+}
+
+impl WgpuWindowedRender for GrayScott {
+    fn render<'a>(&'a mut self, _: &WgpuWindowed<'_>, render_pass: &mut RenderPass<'a>) {
+        self.post.render(render_pass);
+    }
+
+    fn render_encoder(
+        &mut self,
+        wgpu_windowed: &WgpuWindowed<'_>,
+        encoder: &mut CommandEncoder,
+        after: bool,
+    ) {
+        if after {
+            return;
+        }
+
+        let base = &wgpu_windowed.base;
+
+        self.lut.update(base);
+
+        if self.reset {
+            self.reset = false;
+            base.write_texture(&self.textures[self.current], &initial_state());
+        }
+
+        let Params {
+            feed,
+            kill,
+            diffuse_a,
+            diffuse_b,
+        } = self.params;
+        let [x, y] = self.brush.unwrap_or_default();
+
+        let constants = StepConstants {
+            feed,
+            kill,
+            diffuse_a,
+            diffuse_b,
+            delta_time: 1.0,
+            brush: Vec2 { x, y },
+            brush_radius: if self.brush.is_some() {
+                self.brush_radius
+            } else {
+                0.0
+            },
+        };
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&Default::default());
+
+            for _ in 0..self.steps {
+                let pipeline = &self.step_pipelines[self.current];
+                compute_pass.begin(pipeline);
+                compute_pass.pushc(constants.as_std430().as_bytes());
+                compute_pass.dispatch_for_texture(pipeline, &DESC);
+
+                self.current = 1 - self.current;
+            }
+        }
+
+        {
+            let constants = RenderConstants { scale: self.scale };
+
+            let mut render_pass =
+                begin_render_pass(encoder, &[ColorAttachment::new(self.post.input())]);
+            render_pass.begin(&self.render_pipelines[self.current]);
+            render_pass.pushc(constants.as_std430().as_bytes());
+            render_pass.draw(0..3, 0..1);
+        }
+
+        self.post.encode(encoder);
+    }
+}
+
+impl ImguiWgpuRender for GrayScott {
+    fn render_ui(&mut self, ui: &mut imgui::Ui<'_>) {
+        let params = &mut self.params;
+        let steps = &mut self.steps;
+        let scale = &mut self.scale;
+        let brush_radius = &mut self.brush_radius;
+        let reset = &mut self.reset;
+        let lut = &mut self.lut;
+
+        Window::new(im_str!("Gray-Scott"))
+            .always_auto_resize(true)
+            .build(ui, || {
+                for (index, (name, preset)) in Params::PRESETS.iter().enumerate() {
+                    if index > 0 {
+                        ui.same_line(0.0);
+                    }
+                    if ui.small_button(&im_str!("{}", name)) {
+                        *params = *preset;
+                    }
+                }
+
+                let Params {
+                    feed,
+                    kill,
+                    diffuse_a,
+                    diffuse_b,
+                } = params;
+
+                Drag::new(im_str!("Feed"))
+                    .range(0.0..=0.1)
+                    .speed(0.0001)
+                    .display_format(im_str!("%.4f"))
+                    .build(ui, feed);
+                Drag::new(im_str!("Kill"))
+                    .range(0.0..=0.1)
+                    .speed(0.0001)
+                    .display_format(im_str!("%.4f"))
+                    .build(ui, kill);
+                Drag::new(im_str!("Diffuse A"))
+                    .range(0.0..=1.0)
+                    .speed(0.005)
+                    .build(ui, diffuse_a);
+                Drag::new(im_str!("Diffuse B"))
+                    .range(0.0..=1.0)
+                    .speed(0.005)
+                    .build(ui, diffuse_b);
+                Drag::new(im_str!("Steps per Frame"))
+                    .range(0..=64)
+                    .build(ui, steps);
+                ui.separator();
+                Drag::new(im_str!("Brush Radius"))
+                    .range(1.0..=64.0)
+                    .speed(0.1)
+                    .build(ui, brush_radius);
+                *reset |= ui.small_button(im_str!("Reset"));
+                ui.separator();
+                Drag::new(im_str!("Scale"))
+                    .range(0.1..=16.0)
+                    .speed(0.01)
+                    .flags(SliderFlags::LOGARITHMIC)
+                    .build(ui, scale);
+                lut.render_ui(ui);
+            });
+
+        let post = &mut self.post;
+
+        Window::new(im_str!("Post Processing"))
+            .always_auto_resize(true)
+            .build(ui, || post.render_ui(ui));
+    }
+}
+
+impl ActionInput for GrayScott {
+    fn actions(&self, actions: &mut Vec<Action>) {
+        actions.push(RESET);
+    }
+
+    fn action(&mut self, name: &str) {
+        if name == RESET.name {
+            self.reset = true;
+        }
+    }
+}
+
+impl App for GrayScott {
+    fn save_preset(&self, name: &str) -> Result<(), serialize::Error> {
+        serialize::serialize(name, &self.preset())
+    }
+
+    fn load_preset(&mut self, name: &str) -> Result<(), serialize::Error> {
+        self.set_preset(serialize::deserialize(name)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use std::fs;
+use std::path::Path;
+
+use super::rule::Rule;
+
+// initial state for the automaton app, rows go from top to bottom
+pub struct Pattern {
+    pub width: u32,
+    pub height: u32,
+    pub cells: Vec<u8>,
+    pub grayscale: bool, // cells are 0-255 intensities from an image instead of states
+    pub rule: Option<Rule>,
+}
+
+impl Pattern {
+    // larger rle patterns are cropped, they would be cropped by place() anyway
+    pub const MAX_SIZE: u32 = 4096;
+
+    // chooses the format by extension, rle for anything that isn't png
+    // the error is shown in the ui
+    pub fn load(path: &str) -> Result<Self, String> {
+        let is_png = Path::new(path)
+            .extension()
+            .map_or(false, |ext| ext.eq_ignore_ascii_case("png"));
+
+        if is_png {
+            let image = image::open(path).map_err(|err| err.to_string())?;
+            Ok(Self::from_image(image.into_luma8()))
+        } else {
+            let data = fs::read_to_string(path).map_err(|err| err.to_string())?;
+            Self::parse_rle(&data).ok_or_else(|| "invalid rle pattern".to_owned())
+        }
+    }
+
+    fn from_image(image: image::GrayImage) -> Self {
+        Self {
+            width: image.width(),
+            height: image.height(),
+            cells: image.into_raw(),
+            grayscale: true,
+            rule: None,
+        }
+    }
+
+    // https://conwaylife.com/wiki/Run_Length_Encoded
+    // b and o are dead and alive, . and A-X are the states of generations rules
+    pub fn parse_rle(data: &str) -> Option<Self> {
+        let mut lines = data
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        let mut width = 0;
+        let mut height = 0;
+        let mut rule = None;
+
+        for field in lines.next()?.split(',') {
+            let mut pair = field.splitn(2, '=').map(str::trim);
+
+            match (pair.next()?, pair.next()?) {
+                ("x", value) => width = value.parse::<u32>().ok()?.min(Self::MAX_SIZE),
+                ("y", value) => height = value.parse::<u32>().ok()?.min(Self::MAX_SIZE),
+                ("rule", value) => rule = Some(Rule::parse(value)?),
+                _ => {}
+            }
+        }
+
+        let mut cells = vec![0; width as usize * height as usize];
+        let (mut x, mut y) = (0, 0);
+        let mut count = None;
+
+        for c in lines.flat_map(str::chars) {
+            if let Some(digit) = c.to_digit(10) {
+                count = Some(count.unwrap_or(0u32).checked_mul(10)?.checked_add(digit)?);
+                continue;
+            }
+
+            let run = count.take().unwrap_or(1);
+
+            let state = match c {
+                '!' => break,
+                '$' => {
+                    y = y.saturating_add(run);
+                    x = 0;
+                    continue;
+                }
+                'b' | '.' => 0,
+                'o' => 1,
+                'A'..='X' => c as u32 - 'A' as u32 + 1,
+                _ => continue,
+            };
+
+            // the rest of a run past the end of the row is dropped
+            let end = x.saturating_add(run).min(width);
+            if y < height {
+                for x in x..end {
+                    cells[(y * width + x) as usize] = state as u8;
+                }
+            }
+            x = end;
+        }
+
+        Some(Self {
+            width,
+            height,
+            cells,
+            grayscale: false,
+            rule,
+        })
+    }
+
+    // centered in a grid of the given size, with y pointing up to match the rendering
+    pub fn place(&self, width: u32, height: u32) -> Vec<u8> {
+        let mut grid = vec![0; width as usize * height as usize];
+
+        let offset_x = (width as i64 - self.width as i64) / 2;
+        let offset_y = (height as i64 - self.height as i64) / 2;
+
+        for row in 0..self.height as i64 {
+            for column in 0..self.width as i64 {
+                let x = column + offset_x;
+                let y = height as i64 - 1 - (row + offset_y);
+
+                if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+                    continue;
+                }
+
+                grid[(y * width as i64 + x) as usize] =
+                    self.cells[(row * self.width as i64 + column) as usize];
+            }
+        }
+
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: &str = "#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!";
+
+    #[test]
+    fn parse_glider() {
+        let pattern = Pattern::parse_rle(GLIDER).unwrap();
+
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(pattern.cells, [0, 1, 0, 0, 0, 1, 1, 1, 1]);
+        assert_eq!(pattern.rule, Some(Rule::LIFE));
+        assert!(!pattern.grayscale);
+    }
+
+    #[test]
+    fn parse_generations_states() {
+        let pattern = Pattern::parse_rle("x = 3, y = 1, rule = B2/S/C3\n.AB!").unwrap();
+
+        assert_eq!(pattern.cells, [0, 1, 2]);
+        assert_eq!(pattern.rule.unwrap().states, 3);
+    }
+
+    #[test]
+    fn clamps_header_size() {
+        let pattern = Pattern::parse_rle("x = 4000000000, y = 4000000000\no!").unwrap();
+
+        assert_eq!(pattern.width, Pattern::MAX_SIZE);
+        assert_eq!(pattern.height, Pattern::MAX_SIZE);
+    }
+
+    #[test]
+    fn runs_stop_at_row_end() {
+        let pattern = Pattern::parse_rle("x = 2, y = 2\n4000000000o$ob!").unwrap();
+
+        assert_eq!(pattern.cells, [1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn rejects_overflowing_run() {
+        assert!(Pattern::parse_rle("x = 2, y = 2\n99999999999o!").is_none());
+    }
+
+    #[test]
+    fn rejects_missing_header() {
+        assert!(Pattern::parse_rle("").is_none());
+        assert!(Pattern::parse_rle("x = 3, y = 1, rule = B9/S23\n3o!").is_none());
+    }
+}
@@ -0,0 +1,1000 @@
+use std::mem;
+
+use ::wgpu::{
+    Buffer, BufferBindingType, BufferUsage, CommandEncoder, RenderPass, StorageTextureAccess,
+    TextureFormat, TextureUsage,
+};
+use bytemuck::Zeroable;
+use crevice::{
+    std140::{AsStd140, Std140},
+    std430::{AsStd430, Std430, UVec2, Vec2, Vec3},
+};
+use imgui::{ColorEdit, ColorEditFlags, SliderFlags};
+use serde::{Deserialize, Serialize};
+
+use crate::imgui::ImguiWgpuRender;
+use crate::input::{Button, Pointer, PointerInput};
+use crate::keymap::{Action, ActionInput};
+use crate::serialize;
+use crate::util::{
+    align_to, as_bool, texture_size, CreateFromWgpu, InitType, SamplerDesc, TextureDesc,
+};
+use crate::wgpu::{
+    begin_render_pass, AsyncReadback, BindGroupEntry, BufferDesc, ColorAttachment,
+    ComputePipelineDesc, DispatchExt, FullComputePipeline, FullRenderPipeline, Histogram,
+    IndirectDispatch, PipelineExt, ReduceResult, ReduceSource, Reduction, RenderPipelineDesc,
+    TextureResult, WgpuBase, WgpuWindowed, WgpuWindowedRender,
+};
+
+use super::camera::{Camera, CameraConstants};
+use super::exposure::{AutoExposure, ExposureConfig};
+use super::gradient::{Gradient, GradientLut};
+use super::post::{self, EffectSlot, PostStack};
+use super::registry::{App, PAUSE, RESET, STEP};
+
+// how the trail intensity is mapped before the gradient lookup, see shader.frag
+const MAPPING_LINEAR: u32 = 0;
+const MAPPING_LOG: u32 = 1;
+const MAPPING_GAMMA: u32 = 2;
+
+// applied to the exposed trail intensity, see shader.frag
+const TONEMAP_NONE: u32 = 0;
+const TONEMAP_REINHARD: u32 = 1;
+const TONEMAP_ACES: u32 = 2;
+const TONEMAP_AGX: u32 = 3;
+
+// what holding the left mouse button does
+const TOOL_PAINT: u32 = 0;
+const TOOL_ERASE: u32 = 1;
+const TOOL_ATTRACT: u32 = 2;
+const TOOL_REPEL: u32 = 3;
+const TOOL_SPAWN: u32 = 4; // on click only
+
+// after a reset, spawning adds more
+const NUM_AGENTS: u32 = 1000;
+// the agent buffer is allocated for this many, spawning stops when it is full
+const MAX_AGENTS: u32 = 100_000;
+// frames of live agent counts in the population plot
+const POPULATION_HISTORY: usize = 600;
+
+#[derive(AsStd430, Serialize, Deserialize, Clone, Debug)]
+struct FragmentConfig {
+    mapping: u32,
+    gamma: f32,
+    log_scale: f32,
+    flip: u32, // bool
+    offset: f32,
+    exposure: f32, // in stops
+    tonemap: u32,
+}
+
+// shader.frag declares these as one flat block, with the camera members prefixed
+#[derive(AsStd430, Debug)]
+struct RenderConstants {
+    config: FragmentConfig,
+    camera: CameraConstants,
+}
+
+#[derive(AsStd140, Serialize, Deserialize, Clone, Debug, Default)]
+struct ComputeConfig {
+    speed: f32,
+    sensor_dist: f32,
+    sensor_size: u32,
+    sensor_angle: f32,
+    turn_speed: f32,
+}
+
+#[derive(AsStd430, Debug)]
+struct Agent {
+    pos: Vec2,
+    angle: f32,
+    energy: f32,
+}
+
+// draw_agents.comp and agents.glsl:birth
+#[derive(AsStd140, Serialize, Deserialize, Clone, Debug)]
+struct LifecycleConfig {
+    enabled: u32, // bool
+    decay: f32,
+    gain: f32,
+    birth_trail: f32,
+    birth_energy: f32,
+}
+
+impl Default for LifecycleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false as _,
+            decay: 0.1,
+            gain: 0.5,
+            birth_trail: 0.5,
+            birth_energy: 0.8,
+        }
+    }
+}
+
+// brush.comp and draw_agents.comp
+#[derive(AsStd430, Debug)]
+struct PointerConstants {
+    pos: Vec2,
+    radius: f32,
+    strength: f32,
+}
+
+#[derive(AsStd430, Debug)]
+struct InitConstants {
+    size: UVec2,
+    num_agents: u32,
+}
+
+#[derive(AsStd430, Debug)]
+struct SpawnConstants {
+    pos: Vec2,
+    radius: f32,
+    count: u32,
+}
+
+#[derive(Clone, Debug)]
+struct ToolConfig {
+    tool: u32,
+    radius: f32, // in texels
+    strength: f32,
+    spawn_count: u32,
+}
+
+#[derive(AsStd430, Serialize, Deserialize, Clone, Debug)]
+struct DiffuseConfig {
+    attenuate: f32,
+    diffuse: f32,
+}
+
+// everything that can be tweaked from imgui, saved and loaded with serialize.rs
+#[derive(Serialize, Deserialize, Debug)]
+struct Preset {
+    fragment: FragmentConfig,
+    gradient: Gradient,
+    exposure: ExposureConfig,
+    compute: ComputeConfig,
+    diffuse: DiffuseConfig,
+    post: Vec<EffectSlot>,
+    #[serde(default)]
+    seed: u32,
+    #[serde(default)]
+    lifecycle: LifecycleConfig,
+}
+
+// size is the number of agents in use, all alive at first, agents.len() is the capacity
+struct AgentBuffer {
+    size: u32,
+    agents: Vec<<Agent as AsStd430>::Std430Type>,
+}
+
+impl AgentBuffer {
+    fn new(size: u32, capacity: u32) -> Self {
+        Self {
+            size,
+            agents: vec![Zeroable::zeroed(); capacity as _],
+        }
+    }
+
+    fn write(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut writer = crevice::std430::Writer::new(&mut data);
+
+        let agents: &[<Agent as AsStd430>::Std430Type] = &self.agents;
+
+        writer.write_std430(&self.size).unwrap();
+        writer.write_std430(&self.size).unwrap(); // num_alive
+        writer.write(agents).unwrap();
+
+        data
+    }
+}
+
+const DESC: TextureDesc = TextureDesc {
+    format: TextureFormat::R32Float,
+    width: 1920 / 2,
+    height: 1015 / 2,
+};
+
+pub struct Physarum {
+    trail: TextureResult,
+    render_pipelines: [FullRenderPipeline; 2], // indexed by camera.filter
+    init_compute_pipeline: FullComputePipeline,
+    draw_compute_pipelines: [FullComputePipeline; 2], // indexed by lifecycle.enabled
+    diffuse_compute_pipeline: FullComputePipeline,
+    brush_compute_pipeline: FullComputePipeline,
+    spawn_compute_pipeline: FullComputePipeline,
+    birth_compute_pipeline: FullComputePipeline,
+    agent_buffer: Buffer,
+    agent_counts: AsyncReadback<u32>, // num_agents and num_alive from the agent buffer
+    agent_dispatch: IndirectDispatch, // draw_agents.comp for the num_agents in the agent buffer
+    compute_config: ComputeConfig,
+    compute_config_buffer: Buffer,
+    lifecycle_config: LifecycleConfig,
+    lifecycle_config_buffer: Buffer,
+    fragment_config: FragmentConfig,
+    diffuse_config: DiffuseConfig,
+    lut: GradientLut,
+    exposure: AutoExposure,
+    post: PostStack,
+    stats: Reduction,
+    histogram: Histogram,
+    show_stats: bool,
+    stats_result: ReduceResult,
+    histogram_values: Vec<f32>,
+    reset: bool, // clears the trail and reinitializes the agents from seed
+    running: bool,
+    step: bool, // advance once while paused
+    seed: u32,
+    // only for the ui, a frame or two behind the gpu, which keeps track of the agents itself
+    num_agents: u32, // including dead ones
+    num_alive: u32,
+    population: Vec<f32>, // num_alive of the last POPULATION_HISTORY frames with lifecycle
+    tool: ToolConfig,
+    cursor: Option<[f32; 2]>, // in texels, while the left button is held
+    spawn: Option<[f32; 2]>,  // in texels, on click
+    camera: Camera,
+}
+
+impl CreateFromWgpu for Physarum {
+    fn new(wgpu_base: &mut WgpuBase, swapchain_desc: &TextureDesc) -> Self {
+        let desc =
+            DESC.into_2d(TextureUsage::COPY_DST | TextureUsage::SAMPLED | TextureUsage::STORAGE);
+
+        let trail = wgpu_base.texture(
+            &desc,
+            // InitType::Repeated(bytemuck::cast_slice(&[0.3f32, 0.4, 0.5, 0.6, 0.7])),
+            InitType::Zeros,
+        );
+        let tex_view = &trail.view;
+
+        let exposure = AutoExposure::new(wgpu_base, &trail);
+
+        let trail_source = ReduceSource::Texture {
+            texture: &trail,
+            channel: 0,
+        };
+        let stats = wgpu_base.reduction(trail_source);
+        let histogram = wgpu_base.histogram(trail_source, 64);
+
+        let lut = GradientLut::new(
+            wgpu_base,
+            Gradient::two_color([0.0, 0.0, 1.0], [0.5, 1.0, 0.0]),
+        );
+
+        let mut render_pipeline = |filter: bool| {
+            wgpu_base.render_pipeline(RenderPipelineDesc {
+                bind_groups: vec![wgpu_base.bind_group(&[
+                    BindGroupEntry::Texture {
+                        storage: None,
+                        desc: desc.clone(),
+                        view: tex_view,
+                    },
+                    BindGroupEntry::Sampler {
+                        desc: SamplerDesc {
+                            filter,
+                            ..Default::default()
+                        },
+                    },
+                    BindGroupEntry::Texture {
+                        storage: None,
+                        desc: lut.texture.desc.clone(),
+                        view: &lut.texture.view,
+                    },
+                    BindGroupEntry::Sampler {
+                        desc: SamplerDesc {
+                            filter: true,
+                            ..Default::default()
+                        },
+                    },
+                    BindGroupEntry::Buffer {
+                        ty: BufferBindingType::Storage { read_only: true },
+                        buffer: &exposure.buffer,
+                    },
+                ])],
+                shader: "shader.frag",
+                targets: vec![post::FORMAT.into()],
+                push_constants: Some(RenderConstants::std430_size_static() as _),
+            })
+        };
+        let render_pipelines = [render_pipeline(false), render_pipeline(true)];
+
+        let post = PostStack::new(wgpu_base, &DESC, swapchain_desc.format);
+
+        let agent_buffer_data = AgentBuffer::new(NUM_AGENTS, MAX_AGENTS).write();
+        let agent_buffer = wgpu_base.buffer(
+            BufferDesc {
+                size: dbg!(agent_buffer_data.len()),
+                usage: BufferUsage::STORAGE | BufferUsage::COPY_SRC,
+            },
+            InitType::Data(&agent_buffer_data),
+        );
+
+        // int count, then a slot index for each dead agent
+        let free_list_buffer = wgpu_base.buffer(
+            BufferDesc {
+                size: (1 + MAX_AGENTS as usize) * mem::size_of::<u32>(),
+                usage: BufferUsage::STORAGE,
+            },
+            InitType::Zeros,
+        );
+
+        let agent_bind_buffer = BindGroupEntry::Buffer {
+            ty: BufferBindingType::Storage { read_only: false },
+            buffer: &agent_buffer,
+        };
+
+        let free_list_bind_buffer = BindGroupEntry::Buffer {
+            ty: BufferBindingType::Storage { read_only: false },
+            buffer: &free_list_buffer,
+        };
+
+        let rw_tex_bind = BindGroupEntry::Texture {
+            storage: Some(StorageTextureAccess::ReadWrite),
+            desc,
+            view: tex_view,
+        };
+
+        let compute_config_buffer = wgpu_base.buffer(
+            BufferDesc {
+                size: ComputeConfig::std140_size_static(),
+                usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+            },
+            InitType::Uninit,
+        );
+
+        let lifecycle_config_buffer = wgpu_base.buffer(
+            BufferDesc {
+                size: LifecycleConfig::std140_size_static(),
+                usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+            },
+            InitType::Uninit,
+        );
+
+        let lifecycle_bind_buffer = BindGroupEntry::Buffer {
+            ty: BufferBindingType::Uniform,
+            buffer: &lifecycle_config_buffer,
+        };
+
+        let init_compute_pipeline = wgpu_base.compute_pipeline(ComputePipelineDesc {
+            bind_groups: vec![
+                wgpu_base.bind_group(&[agent_bind_buffer.clone(), free_list_bind_buffer.clone()])
+            ],
+            shader: "agents.glsl:init",
+            defines: &[],
+            push_constants: Some(InitConstants::std430_size_static() as _),
+        });
+
+        let mut draw_compute_pipeline = |defines: &'static [&'static str]| {
+            wgpu_base.compute_pipeline(ComputePipelineDesc {
+                bind_groups: vec![wgpu_base.bind_group(&[
+                    rw_tex_bind.clone(),
+                    agent_bind_buffer.clone(),
+                    BindGroupEntry::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        buffer: &compute_config_buffer,
+                    },
+                    free_list_bind_buffer.clone(),
+                    lifecycle_bind_buffer.clone(),
+                ])],
+                shader: "draw_agents.comp",
+                defines,
+                push_constants: Some(PointerConstants::std430_size_static() as _),
+            })
+        };
+        let draw_compute_pipelines = [
+            draw_compute_pipeline(&[]),
+            draw_compute_pipeline(&["LIFECYCLE"]),
+        ];
+
+        let diffuse_compute_pipeline = wgpu_base.compute_pipeline(ComputePipelineDesc {
+            bind_groups: vec![wgpu_base.bind_group(&[rw_tex_bind.clone()])],
+            shader: "diffuse_pass.comp",
+            defines: &[],
+            push_constants: Some(DiffuseConfig::std430_size_static() as _),
+        });
+
+        let brush_compute_pipeline = wgpu_base.compute_pipeline(ComputePipelineDesc {
+            bind_groups: vec![wgpu_base.bind_group(&[rw_tex_bind.clone()])],
+            shader: "brush.comp",
+            defines: &[],
+            push_constants: Some(PointerConstants::std430_size_static() as _),
+        });
+
+        let spawn_compute_pipeline = wgpu_base.compute_pipeline(ComputePipelineDesc {
+            bind_groups: vec![
+                wgpu_base.bind_group(&[agent_bind_buffer.clone(), free_list_bind_buffer.clone()])
+            ],
+            shader: "agents.glsl:spawn",
+            defines: &[],
+            push_constants: Some(SpawnConstants::std430_size_static() as _),
+        });
+
+        let birth_compute_pipeline = wgpu_base.compute_pipeline(ComputePipelineDesc {
+            bind_groups: vec![wgpu_base.bind_group(&[
+                agent_bind_buffer.clone(),
+                free_list_bind_buffer.clone(),
+                rw_tex_bind.clone(),
+                lifecycle_bind_buffer.clone(),
+            ])],
+            shader: "agents.glsl:birth",
+            defines: &[],
+            push_constants: None,
+        });
+
+        let agent_dispatch = wgpu_base.indirect_dispatch(&agent_buffer);
+
+        Self {
+            trail,
+            render_pipelines,
+            init_compute_pipeline,
+            draw_compute_pipelines,
+            diffuse_compute_pipeline,
+            brush_compute_pipeline,
+            spawn_compute_pipeline,
+            birth_compute_pipeline,
+            agent_counts: wgpu_base.async_readback(2),
+            agent_buffer,
+            agent_dispatch,
+            compute_config: ComputeConfig {
+                speed: 60.0,
+                sensor_dist: 1.0,
+                sensor_size: 2,
+                sensor_angle: 30.0,
+                turn_speed: 0.0,
+            },
+            compute_config_buffer,
+            lifecycle_config: Default::default(),
+            lifecycle_config_buffer,
+            fragment_config: FragmentConfig {
+                mapping: MAPPING_LINEAR,
+                gamma: 1.0,
+                log_scale: 10.0,
+                flip: false as _,
+                offset: 0.0,
+                exposure: 0.0,
+                tonemap: TONEMAP_NONE,
+            },
+            diffuse_config: DiffuseConfig {
+                attenuate: 0.5,
+                diffuse: 0.5,
+            },
+            lut,
+            exposure,
+            post,
+            stats,
+            histogram,
+            show_stats: false,
+            stats_result: Default::default(),
+            histogram_values: Vec::new(),
+            reset: true,
+            running: true,
+            step: false,
+            seed: 0,
+            num_agents: NUM_AGENTS,
+            num_alive: NUM_AGENTS,
+            population: Vec::new(),
+            tool: ToolConfig {
+                tool: TOOL_PAINT,
+                radius: 20.0,
+                strength: 0.5,
+                spawn_count: 500,
+            },
+            cursor: None,
+            spawn: None,
+            camera: Camera::new(&DESC),
+        }
+    }
+}
+
+impl Physarum {
+    fn preset(&self) -> Preset {
+        Preset {
+            fragment: self.fragment_config.clone(),
+            gradient: self.lut.gradient().clone(),
+            exposure: self.exposure.config.clone(),
+            compute: self.compute_config.clone(),
+            diffuse: self.diffuse_config.clone(),
+            post: self.post.effects.clone(),
+            seed: self.seed,
+            lifecycle: self.lifecycle_config.clone(),
+        }
+    }
+
+    fn set_preset(&mut self, preset: Preset) {
+        self.fragment_config = preset.fragment;
+        self.lut.set(preset.gradient);
+        self.exposure.config = preset.exposure;
+        self.compute_config = preset.compute;
+        self.diffuse_config = preset.diffuse;
+        self.post.effects = preset.post;
+        self.seed = preset.seed;
+        self.lifecycle_config = preset.lifecycle;
+    }
+
+    fn reset(&mut self, new_seed: bool) {
+        if new_seed {
+            self.seed = self.seed.wrapping_add(1);
+        }
+
+        self.reset = true;
+    }
+
+    fn pointer_constants(&self, tools: [u32; 2]) -> PointerConstants {
+        let ToolConfig {
+            tool,
+            radius,
+            strength,
+            ..
+        } = self.tool;
+
+        let (pos, sign) = match self.cursor {
+            Some(pos) if tool == tools[0] => (pos, 1.0),
+            Some(pos) if tool == tools[1] => (pos, -1.0),
+            _ => ([0.0; 2], 0.0),
+        };
+
+        PointerConstants {
+            pos: Vec2 {
+                x: pos[0],
+                y: pos[1],
+            },
+            radius,
+            strength: strength * sign,
+        }
+    }
+}
+
+impl PointerInput for Physarum {
+    fn pointer(&mut self, pointer: &Pointer) {
+        self.camera.pointer(pointer);
+
+        let texel = self.camera.texel(pointer, DESC.width, DESC.height);
+
+        self.cursor = if pointer.down(Button::Left) {
+            texel
+        } else {
+            None
+        };
+
+        if pointer.pressed(Button::Left) && self.tool.tool == TOOL_SPAWN {
+            self.spawn = texel;
+        }
+    }
+}
+
+impl WgpuWindowedRender for Physarum {
+    fn render<'a>(&'a mut self, _: &WgpuWindowed<'_>, render_pass: &mut RenderPass<'a>) {
+        self.post.render(render_pass);
+    }
+
+    // the same seed replays the same run, as long as the frame count starts over with it
+    fn prepare(&mut self, wgpu_windowed: &mut WgpuWindowed<'_>) {
+        let globals = &mut wgpu_windowed.base.globals;
+        globals.seed = self.seed;
+
+        if self.reset {
+            globals.frame = 0;
+        }
+    }
+
+    fn render_encoder(
+        &mut self,
+        wgpu_windowed: &WgpuWindowed<'_>,
+        encoder: &mut CommandEncoder,
+        after: bool,
+    ) {
+        if after {
+            return;
+        }
+
+        self.lut.update(&wgpu_windowed.base);
+
+        if self.show_stats {
+            // results of a previous frame, whichever reached the cpu
+            let base = &wgpu_windowed.base;
+            if let Some(result) = self.stats.read(base) {
+                self.stats_result = result;
+            }
+            if let Some(counts) = self.histogram.read(base) {
+                self.histogram_values = counts
+                    .into_iter()
+                    .map(|count| (count as f32).ln_1p())
+                    .collect();
+            }
+        }
+
+        let lifecycle = self.lifecycle_config.enabled != 0;
+        if let Some(counts) = self.agent_counts.read(&wgpu_windowed.base) {
+            self.num_agents = counts[0];
+            self.num_alive = counts[1];
+
+            if lifecycle && self.running {
+                if self.population.len() == POPULATION_HISTORY {
+                    self.population.remove(0);
+                }
+                self.population.push(self.num_alive as _);
+            }
+        }
+
+        wgpu_windowed.base.queue.write_buffer(
+            &self.compute_config_buffer,
+            0,
+            self.compute_config.as_std140().as_bytes(),
+        );
+        wgpu_windowed.base.queue.write_buffer(
+            &self.lifecycle_config_buffer,
+            0,
+            self.lifecycle_config.as_std140().as_bytes(),
+        );
+
+        let reset = self.reset;
+        if reset {
+            self.reset = false;
+            self.num_agents = NUM_AGENTS;
+            self.num_alive = NUM_AGENTS;
+            self.population.clear();
+
+            let zeros = vec![0; texture_size(&self.trail.desc)];
+            wgpu_windowed.base.write_texture(&self.trail, &zeros);
+        }
+
+        let advance = self.running || self.step;
+        self.step = false;
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&Default::default());
+
+            if reset {
+                let constants = InitConstants {
+                    size: DESC.size(),
+                    num_agents: NUM_AGENTS,
+                };
+
+                compute_pass.begin(&self.init_compute_pipeline);
+                compute_pass.pushc(constants.as_std430().as_bytes());
+                compute_pass.dispatch_for(&self.init_compute_pipeline, NUM_AGENTS);
+            }
+
+            if let Some([x, y]) = self.spawn.take() {
+                let constants = SpawnConstants {
+                    pos: Vec2 { x, y },
+                    radius: self.tool.radius,
+                    count: self.tool.spawn_count,
+                };
+
+                compute_pass.begin(&self.spawn_compute_pipeline);
+                compute_pass.pushc(constants.as_std430().as_bytes());
+                compute_pass.dispatch_for(&self.spawn_compute_pipeline, constants.count);
+            }
+
+            if advance {
+                compute_pass.begin(&self.diffuse_compute_pipeline);
+                compute_pass.pushc(self.diffuse_config.as_std430().as_bytes());
+                compute_pass.dispatch_for_texture(&self.diffuse_compute_pipeline, &DESC);
+            }
+
+            let brush = self.pointer_constants([TOOL_PAINT, TOOL_ERASE]);
+            if brush.strength != 0.0 {
+                compute_pass.begin(&self.brush_compute_pipeline);
+                compute_pass.pushc(brush.as_std430().as_bytes());
+                compute_pass.dispatch_for_texture(&self.brush_compute_pipeline, &DESC);
+            }
+
+            if advance {
+                let steer = self.pointer_constants([TOOL_ATTRACT, TOOL_REPEL]);
+                let draw = &self.draw_compute_pipelines[lifecycle as usize];
+                self.agent_dispatch.encode(&mut compute_pass, draw);
+
+                compute_pass.begin(draw);
+                compute_pass.pushc(steer.as_std430().as_bytes());
+                compute_pass.dispatch_for_indirect(&self.agent_dispatch);
+
+                if lifecycle {
+                    self.agent_dispatch
+                        .encode(&mut compute_pass, &self.birth_compute_pipeline);
+
+                    compute_pass.begin(&self.birth_compute_pipeline);
+                    compute_pass.dispatch_for_indirect(&self.agent_dispatch);
+                }
+            }
+
+            self.exposure.encode(&mut compute_pass);
+
+            if self.show_stats {
+                self.stats.encode(&mut compute_pass);
+                self.histogram.encode(&mut compute_pass);
+            }
+        }
+
+        self.agent_counts.copy(encoder, &self.agent_buffer, 0);
+
+        if self.show_stats {
+            self.stats.copy(encoder);
+            self.histogram.copy(encoder);
+        }
+
+        {
+            let mut render_pass =
+                begin_render_pass(encoder, &[ColorAttachment::new(self.post.input())]);
+            let constants = RenderConstants {
+                config: self.fragment_config.clone(),
+                camera: self.camera.constants(),
+            };
+
+            render_pass.begin(&self.render_pipelines[self.camera.filter as usize]);
+            render_pass.pushc(constants.as_std430().as_bytes());
+            render_pass.draw(0..3, 0..1);
+        }
+
+        self.post.encode(encoder);
+    }
+}
+
+impl ImguiWgpuRender for Physarum {
+    fn render_ui(&mut self, ui: &mut imgui::Ui<'_>) {
+        use imgui::{im_str, Drag, PlotHistogram, PlotLines, Window};
+
+        // ui.show_demo_window(&mut false);
+
+        let FragmentConfig {
+            mapping,
+            gamma,
+            log_scale,
+            flip,
+            offset,
+            exposure,
+            tonemap,
+        } = &mut self.fragment_config;
+
+        let flip = as_bool(flip);
+        let lut = &mut self.lut;
+        let auto_exposure = &mut self.exposure;
+        let camera = &mut self.camera;
+
+        Window::new(im_str!("Fragment"))
+            .always_auto_resize(true)
+            .build(ui, || {
+                ui.radio_button(im_str!("Linear"), mapping, MAPPING_LINEAR);
+                ui.same_line(0.0);
+                ui.radio_button(im_str!("Log"), mapping, MAPPING_LOG);
+                ui.same_line(0.0);
+                ui.radio_button(im_str!("Gamma"), mapping, MAPPING_GAMMA);
+                match *mapping {
+                    MAPPING_LOG => {
+                        Drag::new(im_str!("Log Scale"))
+                            .range(0.001..=1000.0)
+                            .speed(0.05)
+                            .flags(SliderFlags::LOGARITHMIC)
+                            .build(ui, log_scale);
+                    }
+                    MAPPING_GAMMA => {
+                        Drag::new(im_str!("Gamma##mapping"))
+                            .range(0.01..=10.0)
+                            .speed(0.005)
+                            .flags(SliderFlags::LOGARITHMIC)
+                            .build(ui, gamma);
+                    }
+                    _ => {}
+                }
+                ui.checkbox(im_str!("Flip"), flip);
+                Drag::new(im_str!("Offset"))
+                    .range(0.0..=1.0)
+                    .speed(0.001)
+                    .display_format(im_str!("%.3f"))
+                    .build(ui, offset);
+                ui.separator();
+                Drag::new(im_str!("Exposure"))
+                    .range(-16.0..=16.0)
+                    .speed(0.01)
+                    .build(ui, exposure);
+                ui.radio_button(im_str!("None"), tonemap, TONEMAP_NONE);
+                ui.same_line(0.0);
+                ui.radio_button(im_str!("Reinhard"), tonemap, TONEMAP_REINHARD);
+                ui.same_line(0.0);
+                ui.radio_button(im_str!("ACES"), tonemap, TONEMAP_ACES);
+                ui.same_line(0.0);
+                ui.radio_button(im_str!("AgX"), tonemap, TONEMAP_AGX);
+                auto_exposure.render_ui(ui);
+                ui.separator();
+                lut.render_ui(ui);
+                ui.separator();
+                camera.render_ui(ui);
+            });
+
+        let ComputeConfig {
+            speed,
+            sensor_dist,
+            sensor_size,
+            sensor_angle,
+            turn_speed,
+        } = &mut self.compute_config;
+        let DiffuseConfig { attenuate, diffuse } = &mut self.diffuse_config;
+        let ToolConfig {
+            tool,
+            radius,
+            strength,
+            spawn_count,
+        } = &mut self.tool;
+        let LifecycleConfig {
+            enabled,
+            decay,
+            gain,
+            birth_trail,
+            birth_energy,
+        } = &mut self.lifecycle_config;
+        let lifecycle = as_bool(enabled);
+        let num_agents = self.num_agents;
+        let num_alive = self.num_alive;
+        let population = &self.population;
+        let running = &mut self.running;
+        let step = &mut self.step;
+        let seed = &mut self.seed;
+        let mut reset = None;
+
+        Window::new(im_str!("Compute"))
+            .always_auto_resize(true)
+            .build(ui, || {
+                ui.checkbox(im_str!("Running"), running);
+                ui.same_line(0.0);
+                *step |= ui.small_button(im_str!("Step"));
+                Drag::new(im_str!("Seed")).build(ui, seed);
+                if ui.small_button(im_str!("Reset")) {
+                    reset = Some(true);
+                }
+                ui.same_line(0.0);
+                if ui.small_button(im_str!("Restart")) {
+                    reset = Some(false); // same seed, reproduces the run
+                }
+                ui.separator();
+                Drag::new(im_str!("Speed")).range(0.0..).build(ui, speed);
+                Drag::new(im_str!("Sensor Distance"))
+                    .range(0.0..)
+                    .speed(0.1)
+                    .build(ui, sensor_dist);
+                Drag::new(im_str!("Sensor Size"))
+                    .range(0..)
+                    .speed(0.1)
+                    .build(ui, sensor_size);
+                Drag::new(im_str!("Sensor Angle"))
+                    .range(0.0..=90.0)
+                    .speed(1.0)
+                    .build(ui, sensor_angle);
+                Drag::new(im_str!("Turn Speed"))
+                    .range(0.0..=1.0)
+                    .speed(0.005)
+                    .flags(SliderFlags::LOGARITHMIC)
+                    .build(ui, turn_speed);
+                ui.new_line();
+                Drag::new(im_str!("Attenuate"))
+                    .range(0.0..=1.0)
+                    .speed(0.005)
+                    .flags(SliderFlags::LOGARITHMIC)
+                    .build(ui, attenuate);
+                Drag::new(im_str!("Diffuse"))
+                    .range(0.0..=1.0)
+                    .speed(0.005)
+                    .flags(SliderFlags::LOGARITHMIC)
+                    .build(ui, diffuse);
+                ui.separator();
+                ui.text("Left mouse button:");
+                ui.radio_button(im_str!("Paint"), tool, TOOL_PAINT);
+                ui.same_line(0.0);
+                ui.radio_button(im_str!("Erase"), tool, TOOL_ERASE);
+                ui.same_line(0.0);
+                ui.radio_button(im_str!("Attract"), tool, TOOL_ATTRACT);
+                ui.same_line(0.0);
+                ui.radio_button(im_str!("Repel"), tool, TOOL_REPEL);
+                ui.same_line(0.0);
+                ui.radio_button(im_str!("Spawn"), tool, TOOL_SPAWN);
+                Drag::new(im_str!("Radius"))
+                    .range(1.0..=500.0)
+                    .speed(0.5)
+                    .build(ui, radius);
+                if *tool == TOOL_SPAWN {
+                    Drag::new(im_str!("Spawn Count"))
+                        .range(1..=10_000)
+                        .build(ui, spawn_count);
+                } else {
+                    Drag::new(im_str!("Strength"))
+                        .range(0.0..=10.0)
+                        .speed(0.01)
+                        .build(ui, strength);
+                }
+                ui.text(format!("Agents: {} / {}", num_agents, MAX_AGENTS));
+                ui.separator();
+                ui.checkbox(im_str!("Lifecycle"), lifecycle);
+                if !*lifecycle {
+                    return;
+                }
+                Drag::new(im_str!("Decay"))
+                    .range(0.0..=10.0)
+                    .speed(0.005)
+                    .flags(SliderFlags::LOGARITHMIC)
+                    .build(ui, decay);
+                Drag::new(im_str!("Gain"))
+                    .range(0.0..=10.0)
+                    .speed(0.005)
+                    .flags(SliderFlags::LOGARITHMIC)
+                    .build(ui, gain);
+                Drag::new(im_str!("Birth Trail"))
+                    .range(0.0..=1.0)
+                    .speed(0.005)
+                    .build(ui, birth_trail);
+                Drag::new(im_str!("Birth Energy"))
+                    .range(0.5..=1.0)
+                    .speed(0.005)
+                    .build(ui, birth_energy);
+                ui.text(format!("Alive: {}", num_alive));
+                PlotLines::new(ui, im_str!("Population"), population)
+                    .scale_min(0.0)
+                    .graph_size([256.0, 80.0])
+                    .build();
+            });
+
+        if let Some(new_seed) = reset {
+            self.reset(new_seed);
+        }
+
+        let post = &mut self.post;
+
+        Window::new(im_str!("Post Processing"))
+            .always_auto_resize(true)
+            .build(ui, || post.render_ui(ui));
+
+        let show_stats = &mut self.show_stats;
+        let stats = &self.stats_result;
+        let histogram_values = &self.histogram_values;
+        let (_, histogram_max) = &mut self.histogram.range;
+
+        Window::new(im_str!("Statistics"))
+            .always_auto_resize(true)
+            .build(ui, || {
+                ui.checkbox(im_str!("Enabled"), show_stats);
+                if !*show_stats {
+                    return;
+                }
+
+                ui.text(format!("Mean: {:.4}", stats.mean));
+                ui.text(format!("Min: {:.4}  Max: {:.4}", stats.min, stats.max));
+                ui.text(format!("Sum: {:.1}", stats.sum));
+                PlotHistogram::new(ui, im_str!("Trail Density"), histogram_values)
+                    .overlay_text(im_str!("log(1 + count)"))
+                    .graph_size([256.0, 80.0])
+                    .build();
+                Drag::new(im_str!("Histogram Max"))
+                    .range(0.001..)
+                    .speed(0.01)
+                    .build(ui, histogram_max);
+            });
+    }
+}
+
+impl ActionInput for Physarum {
+    fn actions(&self, actions: &mut Vec<Action>) {
+        actions.extend_from_slice(&[RESET, PAUSE, STEP]);
+    }
+
+    // reset always picks a new seed, Restart in imgui keeps it
+    fn action(&mut self, name: &str) {
+        if name == RESET.name {
+            self.reset(true);
+        } else if name == PAUSE.name {
+            self.running = !self.running;
+        } else if name == STEP.name {
+            self.step = true;
+        }
+    }
+}
+
+impl App for Physarum {
+    fn save_preset(&self, name: &str) -> Result<(), serialize::Error> {
+        serialize::serialize(name, &self.preset())
+    }
+
+    fn load_preset(&mut self, name: &str) -> Result<(), serialize::Error> {
+        self.set_preset(serialize::deserialize(name)?);
+        Ok(())
+    }
+}
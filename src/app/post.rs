@@ -0,0 +1,376 @@
+use ::wgpu::{CommandEncoder, RenderPass, TextureFormat, TextureView};
+use crevice::std430::{AsStd430, Std430, Vec2, Vec4};
+use imgui::{im_str, Drag, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::util::{SamplerDesc, TextureDesc};
+use crate::wgpu::{
+    begin_render_pass, BindGroupEntry, ColorAttachment, FullRenderPipeline, PipelineExt,
+    RenderPipelineDesc, TextureResult, WgpuBase,
+};
+
+// format of the intermediate textures, hdr so that bloom etc can work with values above 1
+pub const FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+#[derive(AsStd430, Debug)]
+struct PostConstants {
+    params: Vec4,
+    texel: Vec2,
+    frame: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum Effect {
+    Bloom {
+        threshold: f32,
+        intensity: f32,
+        radius: f32,
+    },
+    ChromaticAberration {
+        strength: f32,
+    },
+    Vignette {
+        strength: f32,
+        radius: f32,
+        softness: f32,
+    },
+    FilmGrain {
+        strength: f32,
+    },
+    Exposure {
+        exposure: f32,
+        gamma: f32,
+    },
+    Sharpen {
+        strength: f32,
+    },
+}
+
+impl Effect {
+    // one of each effect, in the order of index()
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::Bloom {
+                threshold: 0.8,
+                intensity: 1.0,
+                radius: 8.0,
+            },
+            Self::ChromaticAberration { strength: 2.0 },
+            Self::Vignette {
+                strength: 0.5,
+                radius: 0.5,
+                softness: 0.5,
+            },
+            Self::FilmGrain { strength: 0.05 },
+            Self::Exposure {
+                exposure: 0.0,
+                gamma: 1.0,
+            },
+            Self::Sharpen { strength: 0.5 },
+        ]
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Self::Bloom { .. } => 0,
+            Self::ChromaticAberration { .. } => 1,
+            Self::Vignette { .. } => 2,
+            Self::FilmGrain { .. } => 3,
+            Self::Exposure { .. } => 4,
+            Self::Sharpen { .. } => 5,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Bloom { .. } => "Bloom",
+            Self::ChromaticAberration { .. } => "Chromatic Aberration",
+            Self::Vignette { .. } => "Vignette",
+            Self::FilmGrain { .. } => "Film Grain",
+            Self::Exposure { .. } => "Exposure",
+            Self::Sharpen { .. } => "Sharpen",
+        }
+    }
+
+    fn shader(&self) -> &'static str {
+        match self {
+            Self::Bloom { .. } => "post/bloom.frag",
+            Self::ChromaticAberration { .. } => "post/chromatic_aberration.frag",
+            Self::Vignette { .. } => "post/vignette.frag",
+            Self::FilmGrain { .. } => "post/film_grain.frag",
+            Self::Exposure { .. } => "post/exposure.frag",
+            Self::Sharpen { .. } => "post/sharpen.frag",
+        }
+    }
+
+    // packed into PostConstants.params, see the comment at the top of each shader
+    fn params(&self) -> Vec4 {
+        let [x, y, z, w] = match *self {
+            Self::Bloom {
+                threshold,
+                intensity,
+                radius,
+            } => [threshold, intensity, radius, 0.0],
+            Self::ChromaticAberration { strength } => [strength, 0.0, 0.0, 0.0],
+            Self::Vignette {
+                strength,
+                radius,
+                softness,
+            } => [strength, radius, softness, 0.0],
+            Self::FilmGrain { strength } => [strength, 0.0, 0.0, 0.0],
+            Self::Exposure { exposure, gamma } => [exposure, gamma, 0.0, 0.0],
+            Self::Sharpen { strength } => [strength, 0.0, 0.0, 0.0],
+        };
+
+        Vec4 { x, y, z, w }
+    }
+
+    fn render_ui(&mut self, ui: &Ui<'_>) {
+        match self {
+            Self::Bloom {
+                threshold,
+                intensity,
+                radius,
+            } => {
+                Drag::new(im_str!("Threshold"))
+                    .range(0.0..)
+                    .speed(0.01)
+                    .build(ui, threshold);
+                Drag::new(im_str!("Intensity"))
+                    .range(0.0..)
+                    .speed(0.01)
+                    .build(ui, intensity);
+                Drag::new(im_str!("Radius"))
+                    .range(0.0..=64.0)
+                    .speed(0.1)
+                    .build(ui, radius);
+            }
+            Self::ChromaticAberration { strength } => {
+                Drag::new(im_str!("Strength"))
+                    .range(0.0..=32.0)
+                    .speed(0.05)
+                    .build(ui, strength);
+            }
+            Self::Vignette {
+                strength,
+                radius,
+                softness,
+            } => {
+                Drag::new(im_str!("Strength"))
+                    .range(0.0..=1.0)
+                    .speed(0.005)
+                    .build(ui, strength);
+                Drag::new(im_str!("Radius"))
+                    .range(0.0..=1.0)
+                    .speed(0.005)
+                    .build(ui, radius);
+                Drag::new(im_str!("Softness"))
+                    .range(0.0..=1.0)
+                    .speed(0.005)
+                    .build(ui, softness);
+            }
+            Self::FilmGrain { strength } => {
+                Drag::new(im_str!("Strength"))
+                    .range(0.0..=1.0)
+                    .speed(0.001)
+                    .build(ui, strength);
+            }
+            Self::Exposure { exposure, gamma } => {
+                Drag::new(im_str!("Exposure"))
+                    .range(-8.0..=8.0)
+                    .speed(0.01)
+                    .build(ui, exposure);
+                Drag::new(im_str!("Gamma"))
+                    .range(0.1..=4.0)
+                    .speed(0.005)
+                    .build(ui, gamma);
+            }
+            Self::Sharpen { strength } => {
+                Drag::new(im_str!("Strength"))
+                    .range(0.0..=4.0)
+                    .speed(0.01)
+                    .build(ui, strength);
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EffectSlot {
+    pub effect: Effect,
+    pub enabled: bool,
+}
+
+// default stack, everything disabled
+pub fn default_effects() -> Vec<EffectSlot> {
+    Effect::all()
+        .into_iter()
+        .map(|effect| EffectSlot {
+            effect,
+            enabled: false,
+        })
+        .collect()
+}
+
+pub struct PostStack {
+    pub effects: Vec<EffectSlot>,
+    desc: TextureDesc,
+    targets: [TextureResult; 2],             // ping pong
+    pipelines: Vec<[FullRenderPipeline; 2]>, // indexed by Effect::index(), then by which target is sampled
+    blit: [FullRenderPipeline; 2],
+    output: usize, // which target holds the final image
+    encode_srgb: bool,
+    frame: u32,
+}
+
+impl PostStack {
+    // desc is the size of the intermediate textures, format is ignored
+    // output is the format of the final render pass, the image is kept linear until then
+    pub fn new(wgpu_base: &mut WgpuBase, desc: &TextureDesc, output: TextureFormat) -> Self {
+        let desc = TextureDesc {
+            format: FORMAT,
+            ..desc.clone()
+        };
+
+        let targets = [
+            wgpu_base.render_target(&desc),
+            wgpu_base.render_target(&desc),
+        ];
+
+        let mut pipeline = |shader, source: usize, target: TextureFormat| {
+            let TextureResult { desc, view, .. } = &targets[source];
+
+            wgpu_base.render_pipeline(RenderPipelineDesc {
+                bind_groups: vec![wgpu_base.bind_group(&[
+                    BindGroupEntry::Texture {
+                        storage: None,
+                        desc: desc.clone(),
+                        view,
+                    },
+                    BindGroupEntry::Sampler {
+                        desc: SamplerDesc {
+                            filter: true,
+                            ..Default::default()
+                        },
+                    },
+                ])],
+                shader,
+                targets: vec![target.into()],
+                push_constants: Some(PostConstants::std430_size_static() as _),
+            })
+        };
+
+        let pipelines = Effect::all()
+            .iter()
+            .map(|effect| {
+                [
+                    pipeline(effect.shader(), 0, FORMAT),
+                    pipeline(effect.shader(), 1, FORMAT),
+                ]
+            })
+            .collect();
+
+        let blit = [
+            pipeline("post/blit.frag", 0, output),
+            pipeline("post/blit.frag", 1, output),
+        ];
+
+        Self {
+            effects: default_effects(),
+            desc,
+            targets,
+            pipelines,
+            blit,
+            output: 0,
+            encode_srgb: !output.describe().srgb,
+            frame: 0,
+        }
+    }
+
+    // the scene should be rendered into this before encode()
+    pub fn input(&self) -> &TextureView {
+        &self.targets[0].view
+    }
+
+    pub fn encode(&mut self, encoder: &mut CommandEncoder) {
+        self.frame = self.frame.wrapping_add(1);
+
+        let texel = Vec2 {
+            x: 1.0 / self.desc.width as f32,
+            y: 1.0 / self.desc.height as f32,
+        };
+
+        let mut source = 0;
+
+        for slot in self.effects.iter().filter(|slot| slot.enabled) {
+            let target = 1 - source;
+
+            let constants = PostConstants {
+                params: slot.effect.params(),
+                texel,
+                frame: self.frame,
+            };
+
+            let mut render_pass =
+                begin_render_pass(encoder, &[ColorAttachment::new(&self.targets[target].view)]);
+            render_pass.begin(&self.pipelines[slot.effect.index()][source]);
+            render_pass.pushc(constants.as_std430().as_bytes());
+            render_pass.draw(0..3, 0..1);
+
+            source = target;
+        }
+
+        self.output = source;
+    }
+
+    // draw the final image into the current render pass
+    pub fn render<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        let constants = PostConstants {
+            params: Vec4 {
+                x: self.encode_srgb as u32 as f32,
+                y: 0.0,
+                z: 0.0,
+                w: 0.0,
+            },
+            texel: Vec2 { x: 0.0, y: 0.0 },
+            frame: self.frame,
+        };
+
+        render_pass.begin(&self.blit[self.output]);
+        render_pass.pushc(constants.as_std430().as_bytes());
+        render_pass.draw(0..3, 0..1);
+    }
+
+    pub fn render_ui(&mut self, ui: &Ui<'_>) {
+        let len = self.effects.len();
+        let mut swap = None;
+
+        for (index, slot) in self.effects.iter_mut().enumerate() {
+            let id = ui.push_id(index as i32);
+
+            ui.checkbox(&im_str!("{}", slot.effect.name()), &mut slot.enabled);
+
+            ui.same_line(0.0);
+            if ui.small_button(im_str!("Up")) && index > 0 {
+                swap = Some((index - 1, index));
+            }
+
+            ui.same_line(0.0);
+            if ui.small_button(im_str!("Down")) && index + 1 < len {
+                swap = Some((index, index + 1));
+            }
+
+            if slot.enabled {
+                ui.indent();
+                slot.effect.render_ui(ui);
+                ui.unindent();
+            }
+
+            id.pop(ui);
+        }
+
+        if let Some((a, b)) = swap {
+            self.effects.swap(a, b);
+        }
+    }
+}
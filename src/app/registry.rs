@@ -0,0 +1,244 @@
+use ::wgpu::{Color, CommandEncoder, Operations, RenderPass};
+use imgui::{im_str, ImString, MenuItem};
+use winit::event::VirtualKeyCode;
+
+use crate::imgui::ImguiWgpuRender;
+use crate::input::{Pointer, PointerInput};
+use crate::keymap::{Action, ActionInput, Binding, Modifiers, Trigger};
+use crate::serialize;
+use crate::util::{CreateFromWgpu, TextureDesc};
+use crate::wgpu::{WgpuBase, WgpuWindowed, WgpuWindowedRender};
+
+use super::{Automaton, Fluid, GrayScott, Physarum};
+
+// shared by the apps, so that the same keys do the same thing everywhere
+pub const RESET: Action = Action {
+    name: "reset",
+    description: "Reset the simulation",
+    default: &[Binding::press(VirtualKeyCode::R)],
+};
+
+pub const PAUSE: Action = Action {
+    name: "pause",
+    description: "Pause or resume",
+    default: &[Binding::press(VirtualKeyCode::Space)],
+};
+
+pub const STEP: Action = Action {
+    name: "step",
+    description: "Advance one step while paused",
+    default: &[
+        Binding::press(VirtualKeyCode::Period),
+        Binding {
+            key: VirtualKeyCode::Period,
+            modifiers: Modifiers::SHIFT,
+            trigger: Trigger::Hold,
+        },
+    ],
+};
+
+pub trait App: WgpuWindowedRender + ImguiWgpuRender + PointerInput + ActionInput {
+    // presets are saved to presets/<name>.ron, see serialize.rs
+    fn save_preset(&self, name: &str) -> Result<(), serialize::Error>;
+    fn load_preset(&mut self, name: &str) -> Result<(), serialize::Error>;
+}
+
+pub struct AppInfo {
+    pub name: &'static str,
+    create: fn(&mut WgpuBase, &TextureDesc) -> Box<dyn App>,
+}
+
+fn create<T>(wgpu_base: &mut WgpuBase, desc: &TextureDesc) -> Box<dyn App>
+where
+    T: CreateFromWgpu + App + 'static,
+{
+    Box::new(T::new(wgpu_base, desc))
+}
+
+// the first one is the default
+pub const APPS: [AppInfo; 4] = [
+    AppInfo {
+        name: "physarum",
+        create: create::<Physarum>,
+    },
+    AppInfo {
+        name: "gray_scott",
+        create: create::<GrayScott>,
+    },
+    AppInfo {
+        name: "automaton",
+        create: create::<Automaton>,
+    },
+    AppInfo {
+        name: "fluid",
+        create: create::<Fluid>,
+    },
+];
+
+// the index in APPS of the app named on the command line
+// main.rs checks the name before creating the window, so that a typo fails right away
+pub fn find_app(name: &str) -> Result<usize, String> {
+    APPS.iter().position(|app| app.name == name).ok_or_else(|| {
+        let names: Vec<_> = APPS.iter().map(|app| app.name).collect();
+        format!(
+            "unknown app {:?}, expected one of: {}",
+            name,
+            names.join(", ")
+        )
+    })
+}
+
+// holds one of APPS, which can be switched from the menu bar
+// usage: wetgraphics [app] [preset]
+pub struct Registry {
+    current: usize,
+    app: Option<Box<dyn App>>, // only None while switching
+    next: Option<usize>,       // switched in prepare(), which has mutable access to WgpuBase
+    preset: ImString,
+    preset_error: Option<String>, // of the last save or load, shown in the menu
+}
+
+impl CreateFromWgpu for Registry {
+    fn new(wgpu_base: &mut WgpuBase, desc: &TextureDesc) -> Self {
+        let mut args = std::env::args().skip(1);
+
+        let current = match args.next() {
+            Some(name) => find_app(&name).expect("checked in main"),
+            None => 0,
+        };
+
+        let mut app = (APPS[current].create)(wgpu_base, desc);
+
+        let preset = args.next();
+        let preset_error = preset
+            .as_deref()
+            .and_then(|preset| app.load_preset(preset).err())
+            .map(|err| {
+                eprintln!("{}", err);
+                err.to_string()
+            });
+
+        Self {
+            current,
+            app: Some(app),
+            next: None,
+            preset: Self::preset_name(preset.as_deref().unwrap_or(APPS[current].name)),
+            preset_error,
+        }
+    }
+}
+
+impl Registry {
+    fn app(&mut self) -> &mut dyn App {
+        self.app.as_deref_mut().unwrap()
+    }
+
+    fn preset_name(name: &str) -> ImString {
+        let mut preset = ImString::with_capacity(64);
+        preset.push_str(name);
+        preset
+    }
+}
+
+impl WgpuWindowedRender for Registry {
+    fn render<'a>(
+        &'a mut self,
+        wgpu_windowed: &WgpuWindowed<'_>,
+        render_pass: &mut RenderPass<'a>,
+    ) {
+        self.app().render(wgpu_windowed, render_pass);
+    }
+
+    fn render_encoder(
+        &mut self,
+        wgpu_windowed: &WgpuWindowed<'_>,
+        encoder: &mut CommandEncoder,
+        after: bool,
+    ) {
+        self.app().render_encoder(wgpu_windowed, encoder, after);
+    }
+
+    fn ops(&self) -> Operations<Color> {
+        self.app.as_deref().unwrap().ops()
+    }
+
+    fn prepare(&mut self, wgpu_windowed: &mut WgpuWindowed<'_>) {
+        if let Some(next) = self.next.take() {
+            // drop the old app first, so that both apps' resources aren't alive at the same time
+            self.app = None;
+
+            let desc = wgpu_windowed.desc();
+            self.app = Some((APPS[next].create)(&mut wgpu_windowed.base, &desc));
+            self.current = next;
+            self.preset = Self::preset_name(APPS[next].name);
+            self.preset_error = None;
+        }
+
+        self.app().prepare(wgpu_windowed);
+    }
+}
+
+impl PointerInput for Registry {
+    fn pointer(&mut self, pointer: &Pointer) {
+        self.app().pointer(pointer);
+    }
+}
+
+impl ActionInput for Registry {
+    fn actions(&self, actions: &mut Vec<Action>) {
+        self.app.as_deref().unwrap().actions(actions);
+    }
+
+    fn action(&mut self, name: &str) {
+        self.app().action(name);
+    }
+}
+
+impl ImguiWgpuRender for Registry {
+    fn render_ui(&mut self, ui: &mut imgui::Ui<'_>) {
+        let current = self.current;
+        let next = &mut self.next;
+        let preset = &mut self.preset;
+        let preset_error = &self.preset_error;
+        let mut save = false;
+        let mut load = false;
+
+        ui.main_menu_bar(|| {
+            ui.menu(im_str!("App"), true, || {
+                for (index, app) in APPS.iter().enumerate() {
+                    if MenuItem::new(&im_str!("{}", app.name))
+                        .selected(index == current)
+                        .build(ui)
+                        && index != current
+                    {
+                        *next = Some(index);
+                    }
+                }
+            });
+
+            ui.menu(im_str!("Preset"), true, || {
+                ui.input_text(im_str!("Name"), preset).build();
+                save = MenuItem::new(im_str!("Save")).build(ui);
+                load = MenuItem::new(im_str!("Load")).build(ui);
+
+                if let Some(err) = preset_error {
+                    ui.text_colored([1.0, 0.4, 0.4, 1.0], err);
+                }
+            });
+        });
+
+        let name = self.preset.to_str().to_owned();
+
+        if save {
+            let result = self.app().save_preset(&name);
+            self.preset_error = result.err().map(|err| err.to_string());
+        }
+
+        if load {
+            let result = self.app().load_preset(&name);
+            self.preset_error = result.err().map(|err| err.to_string());
+        }
+
+        self.app().render_ui(ui);
+    }
+}
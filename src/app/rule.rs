@@ -0,0 +1,162 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+// life-like and generations rules, birth and survive are bitmasks over the number of live neighbours
+// states is 2 for life-like rules, cells above state 1 are dying and can't be counted as neighbours
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Rule {
+    pub birth: u32,
+    pub survive: u32,
+    pub states: u32,
+}
+
+impl Rule {
+    pub const LIFE: Self = Self {
+        birth: 1 << 3,
+        survive: 1 << 2 | 1 << 3,
+        states: 2,
+    };
+
+    // the max that fits in a R8Uint texture
+    pub const MAX_STATES: u32 = 255;
+
+    // accepts B3/S23, B2/S/C3 (or G3), and the older S/B and S/B/C forms like 23/3 and 345/2/4
+    pub fn parse(rule: &str) -> Option<Self> {
+        let parts: Vec<&str> = rule.trim().split('/').map(str::trim).collect();
+
+        let lettered = parts
+            .iter()
+            .any(|part| part.starts_with(|c: char| c.is_ascii_alphabetic()));
+
+        let (birth, survive, states) = if lettered {
+            let mut birth = None;
+            let mut survive = None;
+            let mut states = None;
+
+            for part in parts {
+                let mut chars = part.chars();
+                let letter = chars.next()?.to_ascii_uppercase();
+                let rest = chars.as_str();
+
+                match letter {
+                    'B' => birth = Some(Self::neighbours(rest)?),
+                    'S' => survive = Some(Self::neighbours(rest)?),
+                    'C' | 'G' => states = Some(rest.parse().ok()?),
+                    _ => return None,
+                }
+            }
+
+            (birth?, survive?, states.unwrap_or(2))
+        } else {
+            match parts[..] {
+                [survive, birth] => (Self::neighbours(birth)?, Self::neighbours(survive)?, 2),
+                [survive, birth, states] => (
+                    Self::neighbours(birth)?,
+                    Self::neighbours(survive)?,
+                    states.parse().ok()?,
+                ),
+                _ => return None,
+            }
+        };
+
+        if states < 2 || states > Self::MAX_STATES {
+            return None;
+        }
+
+        Some(Self {
+            birth,
+            survive,
+            states,
+        })
+    }
+
+    // "238" -> bits 2, 3 and 8
+    fn neighbours(digits: &str) -> Option<u32> {
+        digits.chars().try_fold(0, |mask, c| match c.to_digit(10) {
+            Some(count) if count <= 8 => Some(mask | 1 << count),
+            _ => None,
+        })
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::LIFE
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |mask: u32| -> String {
+            (0..=8)
+                .filter(|count| mask & 1 << count != 0)
+                .map(|count| std::char::from_digit(count, 10).unwrap())
+                .collect()
+        };
+
+        write!(f, "B{}/S{}", digits(self.birth), digits(self.survive))?;
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_life() {
+        assert_eq!(Rule::parse("B3/S23"), Some(Rule::LIFE));
+        assert_eq!(Rule::parse(" b3 / s23 "), Some(Rule::LIFE));
+        assert_eq!(Rule::parse("23/3"), Some(Rule::LIFE));
+    }
+
+    #[test]
+    fn parse_generations() {
+        let brians_brain = Rule {
+            birth: 1 << 2,
+            survive: 0,
+            states: 3,
+        };
+
+        assert_eq!(Rule::parse("B2/S/C3"), Some(brians_brain));
+        assert_eq!(Rule::parse("B2/S/G3"), Some(brians_brain));
+        assert_eq!(Rule::parse("/2/3"), Some(brians_brain));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for rule in &[
+            "",
+            "B3",
+            "B9/S23",
+            "B3/S23/C1",
+            "B3/S23/C256",
+            "X3/S23",
+            "2/3/4/5",
+        ] {
+            assert_eq!(Rule::parse(rule), None, "{}", rule);
+        }
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for rule in &[
+            "B3/S23",
+            "B36/S23",
+            "B2/S",
+            "B/S012345678",
+            "B2/S/C3",
+            "B2/S345/C4",
+        ] {
+            let parsed = Rule::parse(rule).unwrap();
+
+            assert_eq!(parsed.to_string(), *rule);
+            assert_eq!(Rule::parse(&parsed.to_string()), Some(parsed));
+        }
+    }
+}
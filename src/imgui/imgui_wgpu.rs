@@ -1,13 +1,16 @@
 use imgui::{DrawData, Ui};
-use imgui_wgpu::Renderer;
-use wgpu::{CommandEncoder, RenderPass};
+use imgui_wgpu::{Renderer, RendererConfig};
+use wgpu::{Color, CommandEncoder, Operations, RenderPass, TextureFormat};
 use winit::window::Window;
 
 use super::Imgui;
+use crate::keymap::Help;
+use crate::shaders;
 use crate::wgpu::{WgpuWindowed, WgpuWindowedRender};
 
 pub struct ImguiWgpu<'a> {
     pub base: Imgui<'a>,
+    pub help: Option<Help>, // key bindings overlay, drawn on top of the target's ui
     renderer: Renderer,
 }
 
@@ -15,18 +18,7 @@ impl<'a> ImguiWgpu<'a> {
     pub fn new(window: &'a Window, wgpu_window: &WgpuWindowed<'_>) -> Self {
         let mut base = Imgui::new(window);
 
-        let format = wgpu_window.desc().format;
-
-        let mut config = if format.describe().srgb {
-            println!("chose srgb for imgui_wgpu");
-            // yeah so read the docs, new() is intended for srgb texture because it does not do srgb correction because the srgb texture type already does that correction
-            imgui_wgpu::RendererConfig::new()
-        } else {
-            println!("chose linear for imgui_wgpu");
-            // while new_srgb() _does_ do srgb correction because the linear texture type doesn't
-            imgui_wgpu::RendererConfig::new_srgb()
-        };
-        config.texture_format = format;
+        let config = renderer_config(wgpu_window.desc().format);
 
         let renderer = imgui_wgpu::Renderer::new(
             base.context.get().unwrap(),
@@ -35,7 +27,11 @@ impl<'a> ImguiWgpu<'a> {
             config,
         );
 
-        Self { base, renderer }
+        Self {
+            base,
+            help: None,
+            renderer,
+        }
     }
 
     fn render_impl<'r>(
@@ -65,9 +61,21 @@ impl<'a> ImguiWgpu<'a> {
         T: WgpuWindowedRender + ImguiWgpuRender,
     {
         // draw_data borrows self.base, and render_impl borrows self.renderer, so the mut borrow needs to be split
-        let ImguiWgpu { base, renderer } = self;
+        let ImguiWgpu {
+            base,
+            help,
+            renderer,
+        } = self;
+
+        let draw_data = base.render(|ui| {
+            target.render_ui(ui);
 
-        let draw_data = base.render(|ui| target.render_ui(ui));
+            if let Some(help) = help {
+                help.render_ui(ui);
+            }
+
+            shaders::render_errors(ui, wgpu_windowed.base.shader_errors());
+        });
 
         target.render(wgpu_windowed, render_pass);
 
@@ -113,6 +121,28 @@ where
     ) {
         self.inner.render_encoder(wgpu_windowed, encoder, after);
     }
+
+    fn ops(&self) -> Operations<Color> {
+        self.inner.ops()
+    }
+
+    fn prepare(&mut self, wgpu_windowed: &mut WgpuWindowed<'_>) {
+        self.inner.prepare(wgpu_windowed);
+    }
+}
+
+// imgui colors (style and ColorEdit) are srgb
+fn renderer_config(format: TextureFormat) -> RendererConfig {
+    let mut config = if format.describe().srgb {
+        // new() outputs linear colors, the srgb target then does the encoding
+        RendererConfig::new()
+    } else {
+        // new_srgb() does the srgb encoding in the shader, since a linear target doesn't
+        RendererConfig::new_srgb()
+    };
+    config.texture_format = format;
+
+    config
 }
 
 pub trait ImguiWgpuRender {
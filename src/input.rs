@@ -0,0 +1,219 @@
+use winit::event::{
+    ElementState, Event, MouseButton, MouseScrollDelta, Touch, TouchPhase, WindowEvent,
+};
+
+use crate::util::WindowSize;
+
+// pixels per line for touchpads and other devices that scroll by pixels
+const PIXELS_PER_LINE: f32 = 20.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Button {
+    Left, // also touch
+    Right,
+    Middle,
+}
+
+impl Button {
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+// mouse and touch state of the current frame, collected from winit events by the mainloop
+// everything reads as released/zero while imgui has the mouse
+#[derive(Clone, Debug)]
+pub struct Pointer {
+    pos: Option<[f32; 2]>, // in window pixels with y down, None outside the window
+    delta: [f32; 2],
+    scroll: f32, // in lines, positive is away from the user
+    window_size: [f32; 2],
+    down: [bool; 3],
+    pressed: [bool; 3],
+    released: [bool; 3],
+    captured: bool,
+}
+
+pub trait PointerInput {
+    // called once per frame before rendering
+    fn pointer(&mut self, _pointer: &Pointer) {}
+}
+
+impl Pointer {
+    pub fn new(size: WindowSize) -> Self {
+        Self {
+            pos: None,
+            delta: [0.0; 2],
+            scroll: 0.0,
+            window_size: [size.width as f32, size.height as f32],
+            down: [false; 3],
+            pressed: [false; 3],
+            released: [false; 3],
+            captured: false,
+        }
+    }
+
+    pub fn event(&mut self, event: &Event<'_, ()>) {
+        let event = match event {
+            Event::WindowEvent { event, .. } => event,
+            _ => return,
+        };
+
+        match *event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.move_to([position.x as f32, position.y as f32]);
+            }
+            WindowEvent::CursorLeft { .. } => self.pos = None,
+            WindowEvent::MouseInput { state, button, .. } => {
+                let button = match button {
+                    MouseButton::Left => Button::Left,
+                    MouseButton::Right => Button::Right,
+                    MouseButton::Middle => Button::Middle,
+                    MouseButton::Other(_) => return,
+                };
+
+                self.set_button(button, state == ElementState::Pressed);
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y,
+                    MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / PIXELS_PER_LINE,
+                };
+            }
+            // single touch only, it acts as the left button
+            WindowEvent::Touch(Touch {
+                phase, location, ..
+            }) => {
+                let pos = [location.x as f32, location.y as f32];
+
+                match phase {
+                    TouchPhase::Started => {
+                        self.pos = Some(pos);
+                        self.set_button(Button::Left, true);
+                    }
+                    TouchPhase::Moved => self.move_to(pos),
+                    TouchPhase::Ended | TouchPhase::Cancelled => {
+                        self.move_to(pos);
+                        self.set_button(Button::Left, false);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn resize(&mut self, size: WindowSize) {
+        self.window_size = [size.width as f32, size.height as f32];
+    }
+
+    // called by the mainloop after each frame
+    pub fn end_frame(&mut self) {
+        self.delta = [0.0; 2];
+        self.scroll = 0.0;
+        self.pressed = [false; 3];
+        self.released = [false; 3];
+    }
+
+    // set by the mainloop from imgui's want_capture_mouse
+    pub fn set_captured(&mut self, captured: bool) {
+        self.captured = captured;
+    }
+
+    fn move_to(&mut self, pos: [f32; 2]) {
+        if let Some([x, y]) = self.pos {
+            self.delta[0] += pos[0] - x;
+            self.delta[1] += pos[1] - y;
+        }
+
+        self.pos = Some(pos);
+    }
+
+    fn set_button(&mut self, button: Button, down: bool) {
+        let index = button.index();
+
+        if down && !self.down[index] {
+            self.pressed[index] = true;
+        }
+        if !down && self.down[index] {
+            self.released[index] = true;
+        }
+
+        self.down[index] = down;
+    }
+
+    pub fn captured(&self) -> bool {
+        self.captured
+    }
+
+    pub fn down(&self, button: Button) -> bool {
+        !self.captured && self.down[button.index()]
+    }
+
+    pub fn pressed(&self, button: Button) -> bool {
+        !self.captured && self.pressed[button.index()]
+    }
+
+    // not affected by captured, so that a drag started outside of imgui always ends
+    pub fn released(&self, button: Button) -> bool {
+        self.released[button.index()]
+    }
+
+    pub fn scroll(&self) -> f32 {
+        if self.captured {
+            0.0
+        } else {
+            self.scroll
+        }
+    }
+
+    pub fn window_size(&self) -> [f32; 2] {
+        self.window_size
+    }
+
+    // window pixels with y down, None while captured
+    pub fn pos(&self) -> Option<[f32; 2]> {
+        self.pos.filter(|_| !self.captured)
+    }
+
+    // 0 to 1 over the window with y pointing up, the same as the uv of fullscreen.vert
+    pub fn uv(&self) -> Option<[f32; 2]> {
+        let [x, y] = self.pos()?;
+        let [width, height] = self.window_size;
+
+        Some([x / width, 1.0 - y / height])
+    }
+
+    // movement since the last frame in the same space as uv()
+    pub fn uv_delta(&self) -> [f32; 2] {
+        let [dx, dy] = self.delta;
+        let [width, height] = self.window_size;
+
+        if self.captured {
+            return [0.0; 2];
+        }
+
+        [dx / width, -dy / height]
+    }
+
+    // position in a texture that is stretched over the whole window
+    pub fn texel(&self, width: u32, height: u32) -> Option<[f32; 2]> {
+        let [u, v] = self.uv()?;
+
+        Some([u * width as f32, v * height as f32])
+    }
+
+    pub fn texel_delta(&self, width: u32, height: u32) -> [f32; 2] {
+        let [du, dv] = self.uv_delta();
+
+        [du * width as f32, dv * height as f32]
+    }
+
+    // movement since the last frame while the button is held
+    pub fn drag(&self, button: Button) -> Option<[f32; 2]> {
+        if self.down(button) {
+            Some(self.uv_delta())
+        } else {
+            None
+        }
+    }
+}
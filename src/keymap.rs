@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use imgui::{im_str, Ui, Window};
+use serde::{Deserialize, Serialize};
+use winit::event::{ElementState, ModifiersState, VirtualKeyCode};
+
+use crate::serialize;
+
+// keymap.ron, maps action names to bindings, an action listed there replaces all of its defaults
+// eg: { "screenshot": [(key: F1), (key: S, modifiers: (ctrl: true))], "step": [(key: N, trigger: Hold)] }
+// not a preset, see serialize::read
+const CONFIG: &str = "keymap.ron";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(default)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        ctrl: false,
+        shift: false,
+        alt: false,
+    };
+
+    pub const SHIFT: Self = Self {
+        shift: true,
+        ..Self::NONE
+    };
+}
+
+impl From<ModifiersState> for Modifiers {
+    fn from(state: ModifiersState) -> Self {
+        Self {
+            ctrl: state.ctrl(),
+            shift: state.shift(),
+            alt: state.alt(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Trigger {
+    Press,
+    Release,
+    Hold, // every frame while held
+}
+
+impl Default for Trigger {
+    fn default() -> Self {
+        Self::Press
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Binding {
+    pub key: VirtualKeyCode,
+    #[serde(default)]
+    pub modifiers: Modifiers,
+    #[serde(default)]
+    pub trigger: Trigger,
+}
+
+impl Binding {
+    pub const fn press(key: VirtualKeyCode) -> Self {
+        Self {
+            key,
+            modifiers: Modifiers::NONE,
+            trigger: Trigger::Press,
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Modifiers { ctrl, shift, alt } = self.modifiers;
+
+        if ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if shift {
+            write!(f, "Shift+")?;
+        }
+        if alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{:?}", self.key)?;
+
+        match self.trigger {
+            Trigger::Press => Ok(()),
+            Trigger::Release => write!(f, " (release)"),
+            Trigger::Hold => write!(f, " (hold)"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Action {
+    pub name: &'static str, // used in keymap.ron
+    pub description: &'static str,
+    pub default: &'static [Binding],
+}
+
+// for the app state, the mainloops have the same methods on Mainloop
+pub trait ActionInput {
+    // called every frame, so that the list can change at runtime
+    fn actions(&self, _actions: &mut Vec<Action>) {}
+    fn action(&mut self, _name: &str) {}
+}
+
+pub struct Keymap {
+    overrides: HashMap<String, Vec<Binding>>,
+    actions: Vec<Action>,
+    modifiers: Modifiers,
+    held: Vec<(VirtualKeyCode, Modifiers)>, // with the modifiers at the time of the press
+}
+
+impl Keymap {
+    pub fn new() -> Self {
+        Self {
+            overrides: Self::overrides(),
+            actions: Vec::new(),
+            modifiers: Modifiers::NONE,
+            held: Vec::new(),
+        }
+    }
+
+    // without a keymap.ron there are no overrides, only a broken one is worth reporting
+    fn overrides() -> HashMap<String, Vec<Binding>> {
+        serialize::read(CONFIG).unwrap_or_else(|err| {
+            if !err.is_not_found() {
+                eprintln!("{}", err);
+            }
+            HashMap::new()
+        })
+    }
+
+    pub fn set_actions(&mut self, actions: Vec<Action>) {
+        self.actions = actions;
+    }
+
+    pub fn set_modifiers(&mut self, state: ModifiersState) {
+        self.modifiers = state.into();
+    }
+
+    fn bindings<'a>(&'a self, action: &'a Action) -> &'a [Binding] {
+        self.overrides
+            .get(action.name)
+            .map_or(action.default, Vec::as_slice)
+    }
+
+    fn matching(
+        &self,
+        key: VirtualKeyCode,
+        modifiers: Modifiers,
+        trigger: Trigger,
+    ) -> Vec<&'static str> {
+        let binding = Binding {
+            key,
+            modifiers,
+            trigger,
+        };
+
+        self.actions
+            .iter()
+            .filter(|action| self.bindings(action).contains(&binding))
+            .map(|action| action.name)
+            .collect()
+    }
+
+    // returns the triggered actions, presses should not be passed in while imgui has the keyboard
+    pub fn key(&mut self, key: VirtualKeyCode, state: ElementState) -> Vec<&'static str> {
+        let held = self.held.iter().position(|&(held, _)| held == key);
+
+        match (state, held) {
+            // ignore key repeat
+            (ElementState::Pressed, Some(_)) => Vec::new(),
+            (ElementState::Pressed, None) => {
+                self.held.push((key, self.modifiers));
+                self.matching(key, self.modifiers, Trigger::Press)
+            }
+            (ElementState::Released, Some(index)) => {
+                let (_, modifiers) = self.held.remove(index);
+                self.matching(key, modifiers, Trigger::Release)
+            }
+            (ElementState::Released, None) => Vec::new(),
+        }
+    }
+
+    // actions of held keys, called every frame
+    pub fn held(&self) -> Vec<&'static str> {
+        self.held
+            .iter()
+            .flat_map(|&(key, modifiers)| self.matching(key, modifiers, Trigger::Hold))
+            .collect()
+    }
+
+    pub fn help(&self) -> Help {
+        let lines = self
+            .actions
+            .iter()
+            .map(|action| {
+                let bindings: Vec<_> = self
+                    .bindings(action)
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+
+                (action.description, bindings.join(", "))
+            })
+            .collect();
+
+        Help { lines }
+    }
+}
+
+// snapshot of the current bindings for the imgui overlay
+pub struct Help {
+    lines: Vec<(&'static str, String)>,
+}
+
+impl Help {
+    pub fn render_ui(&self, ui: &Ui<'_>) {
+        let lines = &self.lines;
+
+        Window::new(im_str!("Key Bindings"))
+            .always_auto_resize(true)
+            .build(ui, || {
+                for (description, bindings) in lines {
+                    ui.text(description);
+                    ui.same_line(220.0);
+                    ui.text_disabled(bindings);
+                }
+            });
+    }
+}
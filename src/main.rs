@@ -7,7 +7,12 @@ use crate::window::Window;
 
 mod app;
 mod imgui;
+mod input;
+mod keymap;
 mod mainloop;
+mod noise_check;
+mod rand_check;
+mod random;
 mod serialize;
 mod shaders;
 mod util;
@@ -20,7 +25,26 @@ type MainloopImpl<'a, T> = WgpuScreenshot<'a, T>;
 
 fn main() {
     util::init_log();
+
+    let check: Option<fn() -> bool> = match std::env::args().nth(1).as_deref() {
+        Some("--check-rand") => Some(rand_check::run),
+        Some("--check-noise") => Some(noise_check::run),
+        _ => None,
+    };
+
+    if let Some(check) = check {
+        std::process::exit(if check() { 0 } else { 1 });
+    }
+
+    // usage: wetgraphics [app] [preset], see app::Registry
+    if let Some(name) = std::env::args().nth(1) {
+        if let Err(err) = app::find_app(&name) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
     let (window, winit_window) = Window::new();
-    let mainloop = MainloopImpl::<app::App>::new(&winit_window);
+    let mainloop = MainloopImpl::<app::Registry>::new(&winit_window);
     window.run(&winit_window, mainloop);
 }
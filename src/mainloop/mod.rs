@@ -4,8 +4,9 @@ mod wgpu_screenshot;
 
 use std::time::Duration;
 
-use winit::event::{Event, VirtualKeyCode};
+use winit::event::Event;
 
+use crate::keymap::{Action, Keymap};
 use crate::util::WindowSize;
 
 pub use wgpu_imgui::WgpuImguiWindowMainloop;
@@ -14,7 +15,6 @@ pub use wgpu_screenshot::WgpuScreenshot;
 
 pub trait Mainloop {
     fn event(&mut self, _event: &Event<'_, ()>) {}
-    fn keyboard(&mut self, _key: VirtualKeyCode) {}
     fn update(&mut self, _delta: Duration) {}
     fn render(&mut self) {}
     fn resize(&mut self, _size: WindowSize) {}
@@ -22,4 +22,11 @@ pub trait Mainloop {
     fn ignore_keyboard(&self) -> bool {
         false
     }
+
+    // actions handled by this mainloop and the ones it wraps, see keymap.rs
+    fn actions(&self, _actions: &mut Vec<Action>) {}
+    fn action(&mut self, _name: &str) {}
+
+    // called before render, for the help overlay
+    fn keymap(&mut self, _keymap: &Keymap) {}
 }
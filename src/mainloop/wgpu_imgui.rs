@@ -6,15 +6,44 @@ use winit::{
 };
 
 use crate::imgui::{ImguiWgpu, ImguiWgpuRender};
+use crate::input::{Pointer, PointerInput};
+use crate::keymap::{Action, ActionInput, Binding, Keymap, Modifiers, Trigger};
 use crate::util::{CreateFromWgpu, WindowSize};
 use crate::wgpu::{WgpuWindowed, WgpuWindowedRender};
 
 use super::Mainloop;
 
+const IMGUI_SUSPEND: Action = Action {
+    name: "imgui_suspend",
+    description: "Hide imgui",
+    default: &[Binding::press(VirtualKeyCode::F5)],
+};
+
+const IMGUI_ENABLE: Action = Action {
+    name: "imgui_enable",
+    description: "Show imgui",
+    default: &[Binding::press(VirtualKeyCode::F6)],
+};
+
+const HELP: Action = Action {
+    name: "help",
+    description: "Toggle key bindings",
+    default: &[
+        Binding {
+            key: VirtualKeyCode::Slash,
+            modifiers: Modifiers::SHIFT,
+            trigger: Trigger::Press,
+        },
+        Binding::press(VirtualKeyCode::F12),
+    ],
+};
+
 pub struct WgpuImguiWindowMainloop<'a, T> {
     pub(super) wgpu_window: WgpuWindowed<'a>,
     pub(super) imgui: ImguiWgpu<'a>,
     pub(super) state: T,
+    pub(super) pointer: Pointer,
+    show_help: bool,
 }
 
 impl<'a, T> WgpuImguiWindowMainloop<'a, T>
@@ -30,24 +59,38 @@ where
             wgpu_window,
             imgui,
             state,
+            pointer: Pointer::new(window.inner_size()),
+            show_help: false,
         }
     }
 }
 
+impl<'a, T> WgpuImguiWindowMainloop<'a, T>
+where
+    T: PointerInput,
+{
+    // want_capture_mouse is from the previous imgui frame, which is the one the user clicked on
+    pub(super) fn pointer_input(&mut self) {
+        let captured = self
+            .imgui
+            .base
+            .context
+            .get_ref()
+            .map_or(false, |imgui| imgui.io().want_capture_mouse);
+
+        self.pointer.set_captured(captured);
+        self.state.pointer(&self.pointer);
+        self.pointer.end_frame();
+    }
+}
+
 impl<'a, T> Mainloop for WgpuImguiWindowMainloop<'a, T>
 where
-    T: WgpuWindowedRender + ImguiWgpuRender,
+    T: WgpuWindowedRender + ImguiWgpuRender + PointerInput + ActionInput,
 {
     fn event(&mut self, event: &Event<'_, ()>) {
         self.imgui.base.event(event);
-    }
-
-    fn keyboard(&mut self, key: VirtualKeyCode) {
-        match key {
-            VirtualKeyCode::F5 => self.imgui.base.suspend(),
-            VirtualKeyCode::F6 => self.imgui.base.enable(),
-            _ => {}
-        }
+        self.pointer.event(event);
     }
 
     fn update(&mut self, delta: Duration) {
@@ -57,12 +100,14 @@ where
     }
 
     fn render(&mut self) {
+        self.pointer_input();
         self.wgpu_window
             .render(&mut self.imgui.partial_render(&mut self.state));
     }
 
     fn resize(&mut self, size: WindowSize) {
         self.wgpu_window.resize(Some(size));
+        self.pointer.resize(size);
     }
 
     fn ignore_keyboard(&self) -> bool {
@@ -72,4 +117,29 @@ where
             .get_ref()
             .map_or(false, |imgui| imgui.io().want_capture_keyboard)
     }
+
+    fn actions(&self, actions: &mut Vec<Action>) {
+        actions.extend_from_slice(&[IMGUI_SUSPEND, IMGUI_ENABLE, HELP]);
+        self.state.actions(actions);
+    }
+
+    fn action(&mut self, name: &str) {
+        if name == IMGUI_SUSPEND.name {
+            self.imgui.base.suspend();
+        } else if name == IMGUI_ENABLE.name {
+            self.imgui.base.enable();
+        } else if name == HELP.name {
+            self.show_help = !self.show_help;
+        } else {
+            self.state.action(name);
+        }
+    }
+
+    fn keymap(&mut self, keymap: &Keymap) {
+        self.imgui.help = if self.show_help {
+            Some(keymap.help())
+        } else {
+            None
+        };
+    }
 }
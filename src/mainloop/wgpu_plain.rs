@@ -1,5 +1,7 @@
-use winit::window::Window;
+use winit::{event::Event, window::Window};
 
+use crate::input::{Pointer, PointerInput};
+use crate::keymap::{Action, ActionInput};
 use crate::util::{CreateFromWgpu, WindowSize};
 use crate::wgpu::{WgpuWindowed, WgpuWindowedRender};
 
@@ -8,6 +10,7 @@ use super::Mainloop;
 pub struct WgpuWindowMainloop<'a, T> {
     wgpu_window: WgpuWindowed<'a>,
     state: T,
+    pointer: Pointer,
 }
 
 impl<'a, T> WgpuWindowMainloop<'a, T>
@@ -18,19 +21,38 @@ where
         let mut wgpu_window = WgpuWindowed::new(window);
         let desc = wgpu_window.desc();
         let state = T::new(&mut wgpu_window.base, &desc);
-        Self { wgpu_window, state }
+        Self {
+            wgpu_window,
+            state,
+            pointer: Pointer::new(window.inner_size()),
+        }
     }
 }
 
 impl<'a, T> Mainloop for WgpuWindowMainloop<'a, T>
 where
-    T: WgpuWindowedRender,
+    T: WgpuWindowedRender + PointerInput + ActionInput,
 {
+    fn event(&mut self, event: &Event<'_, ()>) {
+        self.pointer.event(event);
+    }
+
     fn render(&mut self) {
+        self.state.pointer(&self.pointer);
+        self.pointer.end_frame();
         self.wgpu_window.render(&mut self.state);
     }
 
     fn resize(&mut self, size: WindowSize) {
         self.wgpu_window.resize(Some(size));
+        self.pointer.resize(size);
+    }
+
+    fn actions(&self, actions: &mut Vec<Action>) {
+        self.state.actions(actions);
+    }
+
+    fn action(&mut self, name: &str) {
+        self.state.action(name);
     }
 }
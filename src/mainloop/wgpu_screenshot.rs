@@ -2,8 +2,8 @@ use std::time::Duration;
 
 use pollster::FutureExt as _;
 use wgpu::{
-    Buffer, BufferDescriptor, BufferUsage, CommandEncoder, ImageCopyBuffer, ImageCopyTexture,
-    RenderPass, Texture, TextureDescriptor, TextureUsage,
+    Buffer, BufferDescriptor, BufferUsage, Color, CommandEncoder, ImageCopyBuffer,
+    ImageCopyTexture, Operations, RenderPass, Texture, TextureDescriptor, TextureUsage,
 };
 use winit::{
     event::{Event, VirtualKeyCode},
@@ -11,13 +11,21 @@ use winit::{
 };
 
 use crate::imgui::ImguiWgpuRender;
+use crate::input::PointerInput;
+use crate::keymap::{Action, ActionInput, Binding, Keymap};
 use crate::util::{
     texture_image_layout, texture_size, to_image, CreateFromWgpu, InitType, WindowSize,
 };
-use crate::wgpu::{TextureResult, WgpuBase, WgpuBaseRender, WgpuWindowed, WgpuWindowedRender};
+use crate::wgpu::{TextureResult, WgpuWindowed, WgpuWindowedRender};
 
 use super::{Mainloop, WgpuImguiWindowMainloop};
 
+const SCREENSHOT: Action = Action {
+    name: "screenshot",
+    description: "Save a screenshot to out.png",
+    default: &[Binding::press(VirtualKeyCode::F1)],
+};
+
 pub struct WgpuScreenshot<'a, T> {
     should_screenshot: bool,
     inner: WgpuImguiWindowMainloop<'a, T>,
@@ -37,21 +45,12 @@ where
 
 impl<T> Mainloop for WgpuScreenshot<'_, T>
 where
-    T: WgpuWindowedRender + ImguiWgpuRender,
+    T: WgpuWindowedRender + ImguiWgpuRender + PointerInput + ActionInput,
 {
     fn event(&mut self, event: &Event<'_, ()>) {
         self.inner.event(event)
     }
 
-    fn keyboard(&mut self, key: VirtualKeyCode) {
-        self.inner.keyboard(key);
-
-        match key {
-            VirtualKeyCode::F1 => self.should_screenshot = true,
-            _ => {}
-        }
-    }
-
     fn update(&mut self, delta: Duration) {
         self.inner.update(delta)
     }
@@ -64,10 +63,13 @@ where
 
         println!("screenshot");
 
+        self.inner.pointer_input();
+
         let WgpuImguiWindowMainloop {
             wgpu_window,
             imgui,
             state,
+            ..
         } = &mut self.inner;
 
         let base = &wgpu_window.base;
@@ -89,14 +91,14 @@ where
         let mut to_render = imgui.partial_render(state); // todo: deduplicate WgpuImguiWindowMainloop.render()
         let mut renderer = ScreenshotRender {
             inner: &mut to_render,
-            wgpu_window,
             texture: &texture,
             buffer: &buffer,
             desc: &desc,
         };
 
-        base.render(&view, &mut renderer);
+        wgpu_window.render_view(&view, &mut renderer);
 
+        let base = &wgpu_window.base;
         let slice = buffer.slice(..);
 
         // todo: move to new thread, or poll(Maintain::Poll) single threaded across multiple frames?
@@ -116,26 +118,51 @@ where
     fn ignore_keyboard(&self) -> bool {
         self.inner.ignore_keyboard()
     }
+
+    fn actions(&self, actions: &mut Vec<Action>) {
+        actions.push(SCREENSHOT);
+        self.inner.actions(actions);
+    }
+
+    fn action(&mut self, name: &str) {
+        if name == SCREENSHOT.name {
+            self.should_screenshot = true;
+        } else {
+            self.inner.action(name);
+        }
+    }
+
+    fn keymap(&mut self, keymap: &Keymap) {
+        self.inner.keymap(keymap);
+    }
 }
 
 struct ScreenshotRender<'a, T> {
     inner: &'a mut T,
-    wgpu_window: &'a WgpuWindowed<'a>,
     texture: &'a Texture,
     buffer: &'a Buffer,
     desc: &'a TextureDescriptor<'a>,
 }
 
-impl<T> WgpuBaseRender for ScreenshotRender<'_, T>
+impl<T> WgpuWindowedRender for ScreenshotRender<'_, T>
 where
     T: WgpuWindowedRender,
 {
-    fn render<'a>(&'a mut self, _: &WgpuBase, render_pass: &mut RenderPass<'a>) {
-        self.inner.render(self.wgpu_window, render_pass);
+    fn render<'a>(
+        &'a mut self,
+        wgpu_windowed: &WgpuWindowed<'_>,
+        render_pass: &mut RenderPass<'a>,
+    ) {
+        self.inner.render(wgpu_windowed, render_pass);
     }
 
-    fn render_encoder(&mut self, wgpu_base: &WgpuBase, encoder: &mut CommandEncoder, after: bool) {
-        self.inner.render_encoder(self.wgpu_window, encoder, after);
+    fn render_encoder(
+        &mut self,
+        wgpu_windowed: &WgpuWindowed<'_>,
+        encoder: &mut CommandEncoder,
+        after: bool,
+    ) {
+        self.inner.render_encoder(wgpu_windowed, encoder, after);
 
         if !after {
             return;
@@ -160,4 +187,12 @@ where
             self.desc.size,
         )
     }
+
+    fn ops(&self) -> Operations<Color> {
+        self.inner.ops()
+    }
+
+    fn prepare(&mut self, wgpu_windowed: &mut WgpuWindowed<'_>) {
+        self.inner.prepare(wgpu_windowed);
+    }
 }
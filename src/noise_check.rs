@@ -0,0 +1,173 @@
+use std::iter;
+
+use crevice::std430::{AsStd430, Std430};
+use wgpu::{BufferBindingType, BufferUsage};
+
+use crate::util::InitType;
+use crate::wgpu::{
+    BindGroupEntry, BufferDesc, ComputePipelineDesc, DispatchExt, PipelineExt, WgpuBase,
+};
+
+// sanity checks of std/noise.glsl, run with: wetgraphics --check-noise
+// the noises are only roughly -1 to 1, so the bounds are loose and mostly catch nans and typos
+
+const COUNT: u32 = 1 << 16;
+const BLOCKS: u32 = 10; // see noise_check.comp
+const SEED: u32 = 1234;
+
+#[derive(AsStd430)]
+struct CheckConstants {
+    count: u32,
+}
+
+// mean and standard deviation
+fn moments(values: impl Iterator<Item = f32>) -> (f64, f64) {
+    let values: Vec<f64> = values.map(f64::from).collect();
+    let len = values.len() as f64;
+
+    let mean = values.iter().sum::<f64>() / len;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / len;
+
+    (mean, variance.sqrt())
+}
+
+struct Checker {
+    failed: u32,
+}
+
+impl Checker {
+    fn check(&mut self, name: &str, ok: bool, detail: String) {
+        println!("{} {}: {}", if ok { "ok  " } else { "FAIL" }, name, detail);
+
+        if !ok {
+            self.failed += 1;
+        }
+    }
+
+    fn finite(&mut self, name: &str, samples: &[[f32; 4]]) {
+        let finite = samples.iter().flatten().all(|v| v.is_finite());
+        self.check(name, finite, "all finite".to_owned());
+    }
+
+    // the first components of each sample are the 2d, 3d and 4d versions of a scalar noise
+    fn scalar(&mut self, name: &str, samples: &[[f32; 4]], bound: f32) {
+        self.finite(name, samples);
+
+        for (component, dimensions) in (2..=4).enumerate() {
+            let values: Vec<f32> = samples.iter().map(|v| v[component]).collect();
+            let name = format!("{} {}d", name, dimensions);
+
+            let max = values.iter().fold(0.0f32, |max, v| max.max(v.abs()));
+            self.check(
+                &name,
+                max <= bound,
+                format!("max abs {:.4} (bound {})", max, bound),
+            );
+
+            // centered on 0, and not stuck at a constant
+            let (mean, deviation) = moments(values.into_iter());
+            self.check(
+                &name,
+                mean.abs() < 0.1 && deviation > 0.05,
+                format!("mean {:.4}, deviation {:.4}", mean, deviation),
+            );
+        }
+    }
+}
+
+// prints a line per check, returns whether all passed
+pub fn run() -> bool {
+    let mut base = WgpuBase::new();
+    base.globals.seed = SEED;
+    base.write_globals();
+
+    let len = (COUNT * BLOCKS) as usize;
+    let buffer = base.buffer(
+        BufferDesc {
+            size: len * std::mem::size_of::<[f32; 4]>(),
+            usage: BufferUsage::STORAGE | BufferUsage::COPY_SRC,
+        },
+        InitType::Uninit,
+    );
+
+    let pipeline = base.compute_pipeline(ComputePipelineDesc {
+        bind_groups: vec![base.bind_group(&[BindGroupEntry::Buffer {
+            ty: BufferBindingType::Storage { read_only: false },
+            buffer: &buffer,
+        }])],
+        shader: "noise_check.comp",
+        defines: &[],
+        push_constants: Some(CheckConstants::std430_size_static() as _),
+    });
+
+    let mut encoder = base.device.create_command_encoder(&Default::default());
+    {
+        let mut compute_pass = encoder.begin_compute_pass(&Default::default());
+        compute_pass.begin(&pipeline);
+        compute_pass.pushc(CheckConstants { count: COUNT }.as_std430().as_bytes());
+        compute_pass.dispatch_for(&pipeline, COUNT);
+    }
+    base.queue.submit(iter::once(encoder.finish()));
+
+    let samples: Vec<[f32; 4]> = base.readback(&buffer, 0, len);
+    let block = |index: usize| &samples[index * COUNT as usize..(index + 1) * COUNT as usize];
+
+    let mut checker = Checker { failed: 0 };
+
+    // value noise interpolates values in -1 to 1, so it can't leave that range
+    checker.scalar("value_noise", block(0), 1.0 + 1e-4);
+    checker.scalar("gradient_noise", block(1), 1.5);
+    checker.scalar("simplex_noise", block(2), 1.5);
+
+    // a step of 0.001 crosses a cell edge in some samples, where a bad fade or corner would jump
+    let steps = block(3);
+    checker.finite("noise steps", steps);
+    for (component, name) in ["value_noise", "gradient_noise", "simplex_noise"]
+        .iter()
+        .enumerate()
+    {
+        let max = steps.iter().fold(0.0f32, |max, v| max.max(v[component]));
+        checker.check(
+            &format!("{} continuity", name),
+            max < 0.05,
+            format!("max change {:.5} over a step of 0.001", max),
+        );
+    }
+
+    // distances to the nearest and second nearest point, the nearest is at most a cell diagonal
+    let worley = block(4);
+    checker.finite("worley_noise", worley);
+    for (pair, dimensions) in [(0, 2), (2, 3)].iter() {
+        let diagonal = (*dimensions as f32).sqrt();
+        let ordered = worley.iter().all(|v| {
+            let (f1, f2) = (v[*pair], v[*pair + 1]);
+            (0.0..=diagonal).contains(&f1) && f1 <= f2
+        });
+
+        checker.check(
+            &format!("worley_noise {}d", dimensions),
+            ordered,
+            format!("0 <= f1 <= f2, f1 <= {:.3}", diagonal),
+        );
+    }
+
+    for (index, dimensions) in [(5, 2), (6, 3)].iter() {
+        let curl = block(*index);
+        let name = format!("curl_noise {}d", dimensions);
+        checker.finite(&name, curl);
+
+        let (_, deviation) = moments(curl.iter().map(|v| v[0]));
+        checker.check(
+            &name,
+            deviation > 0.05,
+            format!("deviation {:.4}", deviation),
+        );
+    }
+
+    checker.scalar("fbm_value", block(7), 1.0 + 1e-4);
+    checker.scalar("fbm_gradient", block(8), 1.5);
+    checker.scalar("fbm_simplex", block(9), 1.5);
+
+    println!("{} failed", checker.failed);
+    checker.failed == 0
+}
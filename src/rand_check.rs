@@ -0,0 +1,189 @@
+use std::iter;
+
+use crevice::std430::{AsStd430, Std430};
+use wgpu::{BufferBindingType, BufferUsage};
+
+use crate::random;
+use crate::util::InitType;
+use crate::wgpu::{
+    BindGroupEntry, BufferDesc, ComputePipelineDesc, DispatchExt, PipelineExt, WgpuBase,
+};
+
+// statistical checks of std/rand.glsl, run with: wetgraphics --check-rand
+// tolerances are 5 standard errors, so a correct implementation practically never fails
+
+const COUNT: u32 = 1 << 16;
+const BLOCKS: u32 = 7; // see rand_check.comp
+const SEED: u32 = 1234;
+
+#[derive(AsStd430)]
+struct CheckConstants {
+    count: u32,
+}
+
+fn floats(block: &[[u32; 4]]) -> Vec<[f32; 4]> {
+    block
+        .iter()
+        .map(|v| {
+            [
+                f32::from_bits(v[0]),
+                f32::from_bits(v[1]),
+                f32::from_bits(v[2]),
+                f32::from_bits(v[3]),
+            ]
+        })
+        .collect()
+}
+
+// mean and variance
+fn moments(values: impl Iterator<Item = f32>) -> (f64, f64) {
+    let values: Vec<f64> = values.map(f64::from).collect();
+    let len = values.len() as f64;
+
+    let mean = values.iter().sum::<f64>() / len;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / len;
+
+    (mean, variance)
+}
+
+struct Checker {
+    failed: u32,
+}
+
+impl Checker {
+    fn check(&mut self, name: &str, ok: bool, detail: String) {
+        println!("{} {}: {}", if ok { "ok  " } else { "FAIL" }, name, detail);
+
+        if !ok {
+            self.failed += 1;
+        }
+    }
+
+    // expected mean and variance, and the fourth central moment for the variance tolerance
+    fn moments(&mut self, name: &str, values: &[f32], mean: f64, variance: f64, moment4: f64) {
+        let (actual_mean, actual_variance) = moments(values.iter().copied());
+        let len = values.len() as f64;
+
+        let mean_tolerance = 5.0 * (variance / len).sqrt();
+        let variance_tolerance = 5.0 * ((moment4 - variance * variance) / len).sqrt();
+
+        self.check(
+            name,
+            (actual_mean - mean).abs() < mean_tolerance
+                && (actual_variance - variance).abs() < variance_tolerance,
+            format!(
+                "mean {:.5} (expected {:.5}), variance {:.5} (expected {:.5})",
+                actual_mean, mean, actual_variance, variance
+            ),
+        );
+    }
+
+    // all four components are uniform in 0 to 1 exclusive
+    fn uniform(&mut self, name: &str, samples: &[[f32; 4]]) {
+        let in_range = samples.iter().flatten().all(|v| (0.0..1.0).contains(v));
+        self.check(name, in_range, "all in [0, 1)".to_owned());
+
+        for component in 0..4 {
+            let values: Vec<f32> = samples.iter().map(|v| v[component]).collect();
+            let name = format!("{} [{}]", name, component);
+            self.moments(&name, &values, 0.5, 1.0 / 12.0, 1.0 / 80.0);
+        }
+    }
+}
+
+fn length(v: &[f32]) -> f32 {
+    v.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+// prints a line per check, returns whether all passed
+pub fn run() -> bool {
+    let mut base = WgpuBase::new();
+    base.globals.seed = SEED;
+    base.write_globals();
+
+    let len = (COUNT * BLOCKS) as usize;
+    let buffer = base.buffer(
+        BufferDesc {
+            size: len * std::mem::size_of::<[u32; 4]>(),
+            usage: BufferUsage::STORAGE | BufferUsage::COPY_SRC,
+        },
+        InitType::Uninit,
+    );
+
+    let pipeline = base.compute_pipeline(ComputePipelineDesc {
+        bind_groups: vec![base.bind_group(&[BindGroupEntry::Buffer {
+            ty: BufferBindingType::Storage { read_only: false },
+            buffer: &buffer,
+        }])],
+        shader: "rand_check.comp",
+        defines: &[],
+        push_constants: Some(CheckConstants::std430_size_static() as _),
+    });
+
+    let mut encoder = base.device.create_command_encoder(&Default::default());
+    {
+        let mut compute_pass = encoder.begin_compute_pass(&Default::default());
+        compute_pass.begin(&pipeline);
+        compute_pass.pushc(CheckConstants { count: COUNT }.as_std430().as_bytes());
+        compute_pass.dispatch_for(&pipeline, COUNT);
+    }
+    base.queue.submit(iter::once(encoder.finish()));
+
+    let samples: Vec<[u32; 4]> = base.readback(&buffer, 0, len);
+    let block = |index: usize| &samples[index * COUNT as usize..(index + 1) * COUNT as usize];
+
+    let mut checker = Checker { failed: 0 };
+
+    let matches_cpu = block(0)
+        .iter()
+        .enumerate()
+        .all(|(i, &gpu)| gpu == random::rand([i as u32, 0, 0, 0], SEED, 0));
+    checker.check("rand", matches_cpu, "matches random.rs".to_owned());
+
+    checker.uniform("rand_uniform", &floats(block(1)));
+
+    let gaussian: Vec<f32> = floats(block(2)).iter().flatten().copied().collect();
+    checker.moments("rand_gaussian", &gaussian, 0.0, 1.0, 3.0);
+
+    let circle = floats(block(3));
+    let on_circle = circle.iter().all(|v| (length(&v[..2]) - 1.0).abs() < 1e-5);
+    checker.check("rand_unit_vec2", on_circle, "length 1".to_owned());
+    let x: Vec<f32> = circle.iter().map(|v| v[0]).collect();
+    checker.moments("rand_unit_vec2 x", &x, 0.0, 0.5, 3.0 / 8.0);
+
+    let in_disk = circle.iter().all(|v| length(&v[2..]) <= 1.0 + 1e-5);
+    checker.check("rand_disk", in_disk, "length <= 1".to_owned());
+    // the squared radius is uniform in 0 to 1
+    let radius2: Vec<f32> = circle.iter().map(|v| v[2] * v[2] + v[3] * v[3]).collect();
+    checker.moments("rand_disk radius^2", &radius2, 0.5, 1.0 / 12.0, 1.0 / 80.0);
+
+    let sphere = floats(block(4));
+    let on_sphere = sphere.iter().all(|v| (length(&v[..3]) - 1.0).abs() < 1e-5);
+    checker.check("rand_unit_vec3", on_sphere, "length 1".to_owned());
+    // each coordinate is uniform in -1 to 1
+    let z: Vec<f32> = sphere.iter().map(|v| v[2]).collect();
+    checker.moments("rand_unit_vec3 z", &z, 0.0, 1.0 / 3.0, 1.0 / 5.0);
+
+    checker.uniform("randf wide range", &floats(block(5)));
+
+    let near = floats(block(6));
+    let mut distinct: Vec<u32> = near.iter().map(|v| v[0].to_bits()).collect();
+    distinct.sort_unstable();
+    distinct.dedup();
+    let distinct = distinct.len() as f64 / COUNT as f64;
+    // 24 bit outputs, so a few birthday collisions are expected
+    checker.check(
+        "randf neighbouring floats",
+        distinct > 0.99,
+        format!("{:.4} distinct", distinct),
+    );
+
+    let matches_cpu = near.iter().enumerate().all(|(i, gpu)| {
+        let input = f32::from_bits(1.0f32.to_bits() + i as u32);
+        *gpu == random::randf([input, 0.0, 0.0, 0.0], SEED, 0)
+    });
+    checker.check("randf", matches_cpu, "matches random.rs".to_owned());
+
+    println!("{} failed", checker.failed);
+    checker.failed == 0
+}
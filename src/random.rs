@@ -0,0 +1,91 @@
+// cpu version of std/rand.glsl, gives the same numbers as the shaders for the same seed and frame
+
+// http://www.jcgt.org/published/0009/03/02/
+pub fn pcg4d(v: [u32; 4]) -> [u32; 4] {
+    let [mut x, mut y, mut z, mut w] = v;
+
+    x = x.wrapping_mul(1664525).wrapping_add(1013904223);
+    y = y.wrapping_mul(1664525).wrapping_add(1013904223);
+    z = z.wrapping_mul(1664525).wrapping_add(1013904223);
+    w = w.wrapping_mul(1664525).wrapping_add(1013904223);
+
+    x = x.wrapping_add(y.wrapping_mul(w));
+    y = y.wrapping_add(z.wrapping_mul(x));
+    z = z.wrapping_add(x.wrapping_mul(y));
+    w = w.wrapping_add(y.wrapping_mul(z));
+
+    x ^= x >> 16;
+    y ^= y >> 16;
+    z ^= z >> 16;
+    w ^= w >> 16;
+
+    x = x.wrapping_add(y.wrapping_mul(w));
+    y = y.wrapping_add(z.wrapping_mul(x));
+    z = z.wrapping_add(x.wrapping_mul(y));
+    w = w.wrapping_add(y.wrapping_mul(z));
+
+    [x, y, z, w]
+}
+
+// rand() with globals.seed and globals.frame
+pub fn rand(v: [u32; 4], seed: u32, frame: u32) -> [u32; 4] {
+    let key = pcg4d([seed, frame, 0, 0]);
+
+    pcg4d([v[0] ^ key[0], v[1] ^ key[1], v[2] ^ key[2], v[3] ^ key[3]])
+}
+
+// to_unit() in std/rand.glsl, 0 to 1 exclusive
+pub fn to_unit(v: u32) -> f32 {
+    (v >> 8) as f32 * (1.0 / 16_777_216.0)
+}
+
+// randf() with the bits of the input hashed, like floatBitsToUint
+pub fn randf(v: [f32; 4], seed: u32, frame: u32) -> [f32; 4] {
+    let bits = [
+        v[0].to_bits(),
+        v[1].to_bits(),
+        v[2].to_bits(),
+        v[3].to_bits(),
+    ];
+    let [x, y, z, w] = rand(bits, seed, frame);
+
+    [to_unit(x), to_unit(y), to_unit(z), to_unit(w)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // from the paper's reference pcg4d, so that a change here can't silently move
+    // both sides of rand_check.rs
+    #[test]
+    fn pcg4d_vectors() {
+        assert_eq!(
+            pcg4d([0, 0, 0, 0]),
+            [0x0f02_f829, 0x2d56_8769, 0x32b0_c43b, 0xd325_48ea]
+        );
+        assert_eq!(
+            pcg4d([1, 2, 3, 4]),
+            [0x3622_cd16, 0xf114_71d8, 0xe110_9b3f, 0x02b9_4c2f]
+        );
+        assert_eq!(
+            pcg4d([u32::MAX; 4]),
+            [0x974e_d892, 0xc015_dc67, 0x9f95_5760, 0xa1bb_a208]
+        );
+    }
+
+    #[test]
+    fn rand_keys_by_seed_and_frame() {
+        assert_eq!(
+            rand([1, 2, 3, 4], 1234, 5),
+            [0x755c_094f, 0xd442_133c, 0xb791_ca01, 0xd065_321b]
+        );
+    }
+
+    #[test]
+    fn to_unit_range() {
+        assert_eq!(to_unit(0), 0.0);
+        assert!(to_unit(u32::MAX) < 1.0);
+        assert_eq!(to_unit(1 << 31), 0.5);
+    }
+}
@@ -1,17 +1,70 @@
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::fs;
+use std::{fmt, fs, io};
 
-static FILE: &str = "serialize.ron";
+#[derive(Debug)]
+pub enum Error {
+    Io { path: String, err: io::Error },
+    // when loading, also when a field was added or renamed since the file was saved
+    Ron { path: String, err: ron::Error },
+}
+
+impl Error {
+    // usually fine, e.g. when nothing was saved yet
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::Io { err, .. } if err.kind() == io::ErrorKind::NotFound)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, err } => write!(f, "{}: {}", path, err),
+            Self::Ron { path, err } => write!(f, "{}: {}", path, err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// in their own directory, so that no preset name can overwrite another file like keymap.ron
+const PRESETS: &str = "presets";
+
+// each app saves to its own file, so that loading doesn't fail on another app's data
+fn path(name: &str) -> String {
+    format!("{}/{}.ron", PRESETS, name)
+}
+
+// the name is typed in the ui, so it can point to a missing directory or be an invalid path
+pub fn serialize<T: Serialize>(name: &str, data: &T) -> Result<(), Error> {
+    let path = path(name);
 
-pub fn serialize<T: Serialize>(data: &T) {
     let config = ron::ser::PrettyConfig::new();
-    let data = ron::ser::to_string_pretty(data, config).unwrap();
-    fs::write(FILE, &data).unwrap();
+    let data = match ron::ser::to_string_pretty(data, config) {
+        Ok(data) => data,
+        Err(err) => return Err(Error::Ron { path, err }),
+    };
+
+    fs::create_dir_all(PRESETS)
+        .and_then(|_| fs::write(&path, &data))
+        .map_err(|err| Error::Io { path, err })
+}
+
+pub fn deserialize<T: DeserializeOwned>(name: &str) -> Result<T, Error> {
+    read(&path(name))
 }
 
-pub fn deserialize<T: DeserializeOwned>() -> T {
-    let data = fs::read_to_string(FILE).unwrap();
-    let data = ron::de::from_str(&data).unwrap();
+// a file outside of the presets, e.g. keymap.ron
+pub fn read<T: DeserializeOwned>(path: &str) -> Result<T, Error> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) => {
+            let path = path.to_owned();
+            return Err(Error::Io { path, err });
+        }
+    };
 
-    data
+    ron::de::from_str(&data).map_err(|err| Error::Ron {
+        path: path.to_owned(),
+        err,
+    })
 }
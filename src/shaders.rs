@@ -1,25 +1,162 @@
-type Shader = &'static [u8];
+use std::fmt;
+
+use imgui::{im_str, Ui, Window};
+
+#[derive(Clone, Copy)]
+enum Shader {
+    Spirv(&'static [u8]), // compiled from glsl by build.rs
+    // validated by build.rs, compiled by wgpu
+    Wgsl {
+        source: &'static str,
+        workgroup_sizes: &'static [(&'static str, [u32; 3])], // of the compute entry points
+    },
+}
+
 type ShaderResult = Result<Shader, &'static str>;
 
 // static SHADERS: phf::Map<&'static str, ShaderResult>
 include!(concat!(env!("OUT_DIR"), "/codegen_shaders.rs"));
 
-fn lookup(name: &str) -> ShaderResult {
-    *SHADERS.get(name).unwrap_or(&Err("Not Found"))
+#[derive(Clone, Debug)]
+pub enum ShaderError {
+    NotFound(String),
+    // the build script's report, with the file, line and source of each error
+    Compile { name: String, log: &'static str },
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(name) => write!(f, "Shader '{}' not found", name),
+            Self::Compile { name, log } => write!(f, "Could not compile '{}':\n{}", name, log),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+// the name of a shader compiled with extra defines, "NAME" or "NAME=value"
+// every variant has to be declared in the shader with a "//! variant:" line, see build.rs
+pub fn variant_name(name: &str, defines: &[&str]) -> String {
+    if defines.is_empty() {
+        return name.to_owned();
+    }
+
+    let mut defines = defines.to_vec();
+    defines.sort_unstable();
+
+    format!("{}#{}", name, defines.join(","))
+}
+
+pub struct LoadedShader {
+    pub module: wgpu::ShaderModule,
+    pub entry_point: String,
+    pub workgroup_size: Option<[u32; 3]>, // for compute shaders, read from the shader
 }
 
-#[track_caller]
-pub fn load(device: &wgpu::Device, name: &str) -> wgpu::ShaderModule {
-    let shader = match lookup(name) {
-        Ok(shader) => shader,
-        Err(err) => panic!("Could not load shader '{}': {}", name, err),
+// the LocalSize execution mode, from glsl's layout(local_size_x = ..) in
+// with local_size_x_id, this is the default value of the specialization constant
+fn spirv_workgroup_size(spirv: &[u8]) -> Option<[u32; 3]> {
+    const MAGIC: u32 = 0x0723_0203;
+    const OP_EXECUTION_MODE: u32 = 16;
+    const LOCAL_SIZE: u32 = 17;
+
+    let words: Vec<u32> = spirv
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+        .collect();
+
+    if words.first() != Some(&MAGIC) {
+        return None;
+    }
+
+    // instructions start after the 5 word header, the first word is the word count and opcode
+    let mut index = 5;
+    while let Some(&word) = words.get(index) {
+        let count = (word >> 16) as usize;
+
+        if word & 0xffff == OP_EXECUTION_MODE && words.get(index + 2) == Some(&LOCAL_SIZE) {
+            let size = words.get(index + 3..index + 6)?;
+            return Some([size[0], size[1], size[2]]);
+        }
+
+        if count == 0 {
+            return None;
+        }
+        index += count;
+    }
+
+    None
+}
+
+// names are "<file>" for main or "<file>:<entry>" for one of several entry points, see build.rs
+// glsl entry points are compiled as separate shaders, but wgsl is one module with all of them
+fn lookup(name: &str) -> Result<(Shader, &str), ShaderError> {
+    let not_found = || ShaderError::NotFound(name.to_owned());
+
+    let (result, entry_point) = match SHADERS.get(name) {
+        Some(result) => (result, "main"),
+        None => {
+            let (file, entry_point) = name
+                .split_once(':')
+                .filter(|(file, _)| file.ends_with(".wgsl"))
+                .ok_or_else(not_found)?;
+
+            (SHADERS.get(file).ok_or_else(not_found)?, entry_point)
+        }
+    };
+
+    match *result {
+        Ok(shader) => Ok((shader, entry_point)),
+        Err(log) => Err(ShaderError::Compile {
+            name: name.to_owned(),
+            log,
+        }),
+    }
+}
+
+pub fn load(device: &wgpu::Device, name: &str) -> Result<LoadedShader, ShaderError> {
+    let (shader, entry_point) = lookup(name)?;
+
+    let (source, workgroup_size) = match shader {
+        Shader::Spirv(spirv) => (wgpu::util::make_spirv(spirv), spirv_workgroup_size(spirv)),
+        Shader::Wgsl {
+            source,
+            workgroup_sizes,
+        } => (
+            wgpu::ShaderSource::Wgsl(source.into()),
+            workgroup_sizes
+                .iter()
+                .find(|(name, _)| *name == entry_point)
+                .map(|&(_, size)| size),
+        ),
     };
 
     let shader = wgpu::ShaderModuleDescriptor {
         label: Some(name),
-        source: wgpu::util::make_spirv(shader),
+        source,
         flags: wgpu::ShaderFlags::VALIDATION,
     };
 
-    device.create_shader_module(&shader)
+    Ok(LoadedShader {
+        module: device.create_shader_module(&shader),
+        entry_point: entry_point.to_owned(),
+        workgroup_size,
+    })
+}
+
+// shown while there are errors, see WgpuBase::shader_errors
+pub fn render_errors(ui: &Ui<'_>, errors: &[ShaderError]) {
+    if errors.is_empty() {
+        return;
+    }
+
+    Window::new(im_str!("Shader Errors"))
+        .always_auto_resize(true)
+        .build(ui, || {
+            for error in errors {
+                ui.text_colored([1.0, 0.4, 0.4, 1.0], error.to_string());
+                ui.separator();
+            }
+        });
 }
@@ -0,0 +1,176 @@
+#version 450
+
+// the passes that add and remove agents, draw_agents.comp moves them
+// every entry binds the agent buffer and free list first, birth also reads the trail
+
+//! entry: compute init
+//! entry: compute spawn
+//! entry: compute birth
+
+#include "shared_agents.glsl"
+
+layout(local_size_x = 64) in;
+
+layout(set = 0, binding = 0, std430) buffer Data {
+    uint num_agents;  // including dead ones, whose slots are in free_list
+    uint num_alive;
+    Agent agents[];
+}
+data;
+
+// slots of dead agents, pushed by draw_agents.comp
+layout(set = 0, binding = 1, std430) buffer FreeList {
+    int count;
+    uint slots[];
+}
+free_list;
+
+#include <consts.glsl>
+#include <rand.glsl>
+
+const uint NO_SLOT = ~0u;
+
+// a dead agent's slot, or one past the others while the buffer has room
+// nothing may free slots in the same dispatch, since undoing an empty pop assumes that
+uint take_slot() {
+    int top = atomicAdd(free_list.count, -1) - 1;
+    if (top >= 0) {
+        return free_list.slots[top];
+    }
+
+    atomicAdd(free_list.count, 1);
+
+    uint slot = atomicAdd(data.num_agents, 1);
+    if (slot >= data.agents.length()) {
+        atomicAdd(data.num_agents, uint(-1));
+        return NO_SLOT;
+    }
+
+    return slot;
+}
+
+#ifdef ENTRY_init
+
+layout(push_constant, std430) uniform PushConstants {
+    uvec2 size;
+    uint num_agents;  // also resets the count, after agents were spawned
+}
+pushc;
+
+void main() {
+    uint index = gl_GlobalInvocationID.x;
+
+    if (index >= pushc.num_agents) {
+        return;
+    }
+
+    if (index == 0) {
+        data.num_agents = pushc.num_agents;
+        data.num_alive = pushc.num_agents;
+        free_list.count = 0;
+    }
+
+    vec2 low = vec2(0.25);
+    vec2 high = vec2(0.75);
+
+    vec4 random = rand_uniform(index);
+
+    Agent agent;
+
+    vec2 norm_pos = random.xy;
+    norm_pos = mix(low, high, norm_pos);
+    agent.pos = vec2(norm_pos * pushc.size);
+
+    agent.angle = random.z * TAU;
+    agent.energy = mix(0.5, 1.0, random.w);  // so that they don't all run out at once
+
+    data.agents[index] = agent;
+}
+
+#endif
+
+#ifdef ENTRY_spawn
+
+// adds agents in a disk around the cursor, in free slots or after the others
+// stops when the buffer is full, so the cpu doesn't need to know how many there are
+layout(push_constant, std430) uniform PushConstants {
+    vec2 pos;  // in texels
+    float radius;
+    uint count;
+}
+pushc;
+
+void main() {
+    uint index = gl_GlobalInvocationID.x;
+
+    if (index >= pushc.count) {
+        return;
+    }
+
+    uint slot = take_slot();
+    if (slot == NO_SLOT) {
+        return;
+    }
+
+    Agent agent;
+    agent.pos = pushc.pos + rand_disk(index) * pushc.radius;
+    agent.angle = rand_uniform(uvec4(index, 1, 0, 0)).x * TAU;
+    agent.energy = 1.0;
+
+    data.agents[slot] = agent;
+
+    atomicAdd(data.num_alive, 1);
+}
+
+#endif
+
+#ifdef ENTRY_birth
+
+// agents with enough energy in dense trail split in two, the child takes a free slot
+// runs after draw_agents.comp, so that slots aren't freed while this takes them
+
+layout(set = 0, binding = 2, r32f) restrict uniform image2D trail_tex;
+
+layout(set = 0, binding = 3, std140) uniform Lifecycle {
+    uint enabled;  // bool
+    float decay;
+    float gain;
+    float birth_trail;
+    float birth_energy;  // at least 0.5, so that neither half can split again right away
+}
+lifecycle;
+
+void main() {
+    uint index = gl_GlobalInvocationID.x;
+
+    if (index >= data.num_agents) {
+        return;
+    }
+
+    Agent agent = data.agents[index];
+
+    if (agent.energy <= lifecycle.birth_energy) {
+        return;
+    }
+
+    float trail = imageLoad(trail_tex, ivec2(agent.pos + 0.5)).x;
+    if (trail < lifecycle.birth_trail) {
+        return;
+    }
+
+    uint slot = take_slot();
+    if (slot == NO_SLOT) {
+        return;
+    }
+
+    agent.energy *= 0.5;
+    data.agents[index] = agent;
+
+    Agent child = agent;
+    child.angle = randf(vec4(agent.pos, agent.energy, index)).x * TAU;
+    data.agents[slot] = child;
+
+    atomicAdd(data.num_alive, 1);
+}
+
+#endif
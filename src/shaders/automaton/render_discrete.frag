@@ -0,0 +1,27 @@
+#version 450
+
+#extension GL_EXT_samplerless_texture_functions : require
+
+layout(location = 0) in vec2 uv;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform utexture2D state_tex;
+layout(set = 0, binding = 1) uniform texture1D lut_tex;
+layout(set = 0, binding = 2) uniform sampler lut_smp;
+
+layout(std430, push_constant) uniform PushConstants {
+    uint states;
+}
+pushc;
+
+void main() {
+    ivec2 size = textureSize(state_tex, 0);
+    uint state = texelFetch(state_tex, min(ivec2(uv * vec2(size)), size - 1), 0).x;
+
+    // alive is 1, dying states fade out towards 0
+    float f = state == 0 ? 0.0 : 1.0 - float(state - 1) / float(pushc.states - 1);
+
+    vec3 color = texture(sampler1D(lut_tex, lut_smp), f).rgb;
+
+    f_color = vec4(color, 1.0);
+}
@@ -0,0 +1,24 @@
+#version 450
+
+#extension GL_EXT_samplerless_texture_functions : require
+
+layout(location = 0) in vec2 uv;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform texture2D state_tex;
+layout(set = 0, binding = 1) uniform texture1D lut_tex;
+layout(set = 0, binding = 2) uniform sampler lut_smp;
+
+layout(std430, push_constant) uniform PushConstants {
+    uint states;  // unused, shared with render_discrete.frag
+}
+pushc;
+
+void main() {
+    ivec2 size = textureSize(state_tex, 0);
+    float f = texelFetch(state_tex, min(ivec2(uv * vec2(size)), size - 1), 0).x;
+
+    vec3 color = texture(sampler1D(lut_tex, lut_smp), clamp(f, 0.0, 1.0)).rgb;
+
+    f_color = vec4(color, 1.0);
+}
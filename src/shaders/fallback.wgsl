@@ -0,0 +1,13 @@
+// stand-ins for shaders that failed to compile, see src/wgpu/shaders.rs
+// loaded at startup, so a broken fallback shows up right away and not with the first error
+
+// dispatch_for() and indirect args size the dispatch by this, so it has to be as large as
+// the real shaders' workgroups, or the workgroup counts can go past the 65535 limit
+[[stage(compute), workgroup_size(16, 16)]]
+fn cs_main() {
+}
+
+[[stage(fragment)]]
+fn fs_main() -> [[location(0)]] vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 1.0, 1.0);
+}
@@ -0,0 +1,27 @@
+#ifndef FLUID
+#define FLUID
+
+// shared helpers of the fluid passes, the grid spacing is 1 texel
+// every pass reads its neighbours with clamp_uv(), which acts as a closed box at the edges
+
+layout(local_size_x = 16, local_size_y = 16) in;
+
+ivec2 clamp_uv(ivec2 uv, ivec2 size) {
+    return clamp(uv, ivec2(0), size - 1);
+}
+
+#define GUARD(tex)                                \
+    ivec2 uv = ivec2(gl_GlobalInvocationID.xy);   \
+    ivec2 size = imageSize(tex);                  \
+    if (uv.x >= size.x || uv.y >= size.y) {       \
+        return;                                   \
+    }
+
+// left, right, bottom, top neighbours of a single channel
+#define NEIGHBOURS(tex, channel)                                         \
+    float L = imageLoad(tex, clamp_uv(uv - ivec2(1, 0), size)).channel; \
+    float R = imageLoad(tex, clamp_uv(uv + ivec2(1, 0), size)).channel; \
+    float B = imageLoad(tex, clamp_uv(uv - ivec2(0, 1), size)).channel; \
+    float T = imageLoad(tex, clamp_uv(uv + ivec2(0, 1), size)).channel;
+
+#endif
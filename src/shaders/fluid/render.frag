@@ -0,0 +1,16 @@
+#version 450
+
+#extension GL_EXT_samplerless_texture_functions : require
+
+layout(location = 0) in vec2 uv;
+layout(location = 0) out vec4 f_color;
+
+// rgba32f can't be filtered, the dye is read with texelFetch
+layout(set = 0, binding = 0) uniform texture2D dye_tex;
+
+void main() {
+    ivec2 size = textureSize(dye_tex, 0);
+    vec3 dye = texelFetch(dye_tex, min(ivec2(uv * vec2(size)), size - 1), 0).rgb;
+
+    f_color = vec4(max(dye, 0.0), 1.0);
+}
@@ -0,0 +1,23 @@
+#version 450
+
+layout(location = 0) in vec2 uv;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform texture2D state_tex;
+layout(set = 0, binding = 1) uniform sampler state_smp;
+layout(set = 0, binding = 2) uniform texture1D lut_tex;
+layout(set = 0, binding = 3) uniform sampler lut_smp;
+
+layout(std430, push_constant) uniform PushConstants {
+    float scale;  // b is rarely above 0.5, stretch it over the gradient
+}
+pushc;
+
+void main() {
+    float b = texture(sampler2D(state_tex, state_smp), uv).y;
+    float f = clamp(b * pushc.scale, 0.0, 1.0);
+
+    vec3 color = texture(sampler1D(lut_tex, lut_smp), f).rgb;
+
+    f_color = vec4(color, 1.0);
+}
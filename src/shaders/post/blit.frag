@@ -0,0 +1,17 @@
+#version 450
+
+#include "post.glsl"
+
+#include <color.glsl>
+
+// params: x = 1 if the output is a non-srgb format, so the encoding has to be done manually
+
+void main() {
+    vec4 color = sample_input(screen_uv());
+
+    if (pushc.params.x > 0.5) {
+        color.rgb = linear_to_srgb(color.rgb);
+    }
+
+    f_color = color;
+}
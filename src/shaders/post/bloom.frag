@@ -0,0 +1,27 @@
+#version 450
+
+#include "post.glsl"
+
+// params: x = threshold, y = intensity, z = radius in texels
+
+const int TAPS = 4;
+
+void main() {
+    vec2 st = screen_uv();
+    vec3 color = sample_input(st).rgb;
+
+    vec3 bloom = vec3(0);
+    float total = 0;
+    for (int dx = -TAPS; dx <= TAPS; dx++) {
+        for (int dy = -TAPS; dy <= TAPS; dy++) {
+            vec2 offset = vec2(dx, dy) / TAPS;
+            float weight = exp(-2.0 * dot(offset, offset));
+
+            vec3 tap = sample_input(st + offset * pushc.params.z * pushc.texel).rgb;
+            bloom += max(tap - pushc.params.x, 0.0) * weight;
+            total += weight;
+        }
+    }
+
+    f_color = vec4(color + bloom / total * pushc.params.y, 1.0);
+}
@@ -0,0 +1,16 @@
+#version 450
+
+#include "post.glsl"
+
+// params: x = strength in texels at the corners
+
+void main() {
+    vec2 st = screen_uv();
+    vec2 offset = (st - 0.5) * 2.0 * pushc.params.x * pushc.texel;
+
+    float r = sample_input(st + offset).r;
+    float g = sample_input(st).g;
+    float b = sample_input(st - offset).b;
+
+    f_color = vec4(r, g, b, 1.0);
+}
@@ -0,0 +1,14 @@
+#version 450
+
+#include "post.glsl"
+
+// params: x = exposure in stops, y = gamma
+
+void main() {
+    vec3 color = sample_input(screen_uv()).rgb;
+
+    color *= exp2(pushc.params.x);
+    color = pow(max(color, 0.0), vec3(1.0 / pushc.params.y));
+
+    f_color = vec4(color, 1.0);
+}
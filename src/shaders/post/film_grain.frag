@@ -0,0 +1,18 @@
+#version 450
+
+#include "post.glsl"
+
+#include <consts.glsl>
+#include <rand.glsl>
+
+// params: x = strength
+
+void main() {
+    vec2 st = screen_uv();
+    vec3 color = sample_input(st).rgb;
+
+    float noise = rand(uvec3(gl_FragCoord.xy, pushc.frame)).x / uint_MAXf;
+    color += (noise - 0.5) * pushc.params.x;
+
+    f_color = vec4(max(color, 0.0), 1.0);
+}
@@ -0,0 +1,32 @@
+#ifndef POST
+#define POST
+
+// shared interface of the fullscreen post-processing passes
+
+layout(location = 0) in vec2 in_uv;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform texture2D input_tex;
+layout(set = 0, binding = 1) uniform sampler input_smp;
+
+layout(std430, push_constant) uniform PushConstants {
+    vec4 params;  // meaning depends on the effect
+    vec2 texel;   // 1 / size of input_tex
+    uint frame;
+}
+pushc;
+
+// fullscreen.vert has uv.y pointing up, flip it so that each pass doesn't mirror the image
+vec2 screen_uv() {
+    return vec2(in_uv.x, 1.0 - in_uv.y);
+}
+
+vec4 sample_input(vec2 st) {
+    return texture(sampler2D(input_tex, input_smp), st);
+}
+
+float luminance(vec3 color) {
+    return dot(color, vec3(0.2126, 0.7152, 0.0722));
+}
+
+#endif
@@ -0,0 +1,20 @@
+#version 450
+
+#include "post.glsl"
+
+// params: x = strength
+
+void main() {
+    vec2 st = screen_uv();
+    vec2 texel = pushc.texel;
+
+    vec3 center = sample_input(st).rgb;
+    vec3 neighbors = sample_input(st + vec2(texel.x, 0)).rgb +
+                     sample_input(st - vec2(texel.x, 0)).rgb +
+                     sample_input(st + vec2(0, texel.y)).rgb +
+                     sample_input(st - vec2(0, texel.y)).rgb;
+
+    vec3 color = center + (center * 4.0 - neighbors) * pushc.params.x;
+
+    f_color = vec4(max(color, 0.0), 1.0);
+}
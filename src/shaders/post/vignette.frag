@@ -0,0 +1,15 @@
+#version 450
+
+#include "post.glsl"
+
+// params: x = strength, y = radius, z = softness
+
+void main() {
+    vec2 st = screen_uv();
+    vec3 color = sample_input(st).rgb;
+
+    float dist = length(st - 0.5) * sqrt(2.0);
+    float falloff = smoothstep(pushc.params.y, pushc.params.y + pushc.params.z, dist);
+
+    f_color = vec4(color * (1.0 - falloff * pushc.params.x), 1.0);
+}
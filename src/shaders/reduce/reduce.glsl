@@ -0,0 +1,49 @@
+#ifndef REDUCE
+#define REDUCE
+
+// running statistics of a set of floats: x = sum, y = min, z = max, w = count
+
+vec4 reduce_identity() {
+    float inf = uintBitsToFloat(0x7F800000u);
+    return vec4(0, inf, -inf, 0);
+}
+
+vec4 reduce_value(float value) {
+    return vec4(value, value, value, 1);
+}
+
+vec4 reduce_combine(vec4 a, vec4 b) {
+    return vec4(a.x + b.x, min(a.y, b.y), max(a.z, b.z), a.w + b.w);
+}
+
+// the invocations in the including shader's workgroup, declare local_size before the include
+// this has to be a power of 2
+const uint REDUCE_SIZE = gl_WorkGroupSize.x * gl_WorkGroupSize.y * gl_WorkGroupSize.z;
+
+// the result is only valid in invocation 0
+shared vec4 reduce_scratch[REDUCE_SIZE];
+
+vec4 workgroup_reduce(vec4 value) {
+    uint local = gl_LocalInvocationIndex;
+
+    reduce_scratch[local] = value;
+    barrier();
+
+    for (uint stride = REDUCE_SIZE / 2; stride > 0; stride >>= 1) {
+        if (local < stride) {
+            reduce_scratch[local] =
+                reduce_combine(reduce_scratch[local], reduce_scratch[local + stride]);
+        }
+        barrier();
+    }
+
+    return reduce_scratch[0];
+}
+
+// which histogram bin a value falls into, values outside of [low, high] are clamped
+uint histogram_bin(float value, float low, float high, uint bins) {
+    float norm = (value - low) / max(high - low, 1e-20);
+    return uint(clamp(int(norm * bins), 0, int(bins) - 1));
+}
+
+#endif
@@ -7,12 +7,36 @@ layout(location = 0) out vec4 f_color;
 
 layout(set = 0, binding = 0) uniform texture2D input_tex;
 layout(set = 0, binding = 1) uniform sampler input_smp;
+layout(set = 0, binding = 2) uniform texture1D lut_tex;
+layout(set = 0, binding = 3) uniform sampler lut_smp;
+
+layout(set = 0, binding = 4, std430) readonly buffer Exposure {
+    float average;
+    float maximum;
+    float scale;  // from auto exposure, 1 if disabled
+}
+exposure;
+
+const uint MAPPING_LINEAR = 0;
+const uint MAPPING_LOG = 1;
+const uint MAPPING_GAMMA = 2;
+
+const uint TONEMAP_NONE = 0;
+const uint TONEMAP_REINHARD = 1;
+const uint TONEMAP_ACES = 2;
+const uint TONEMAP_AGX = 3;
 
 layout(std430, push_constant) uniform PushConstants {
-    vec3 foreground;
-    vec3 background;
+    uint mapping;
+    float gamma;
+    float log_scale;
     bool front;
     float offset;
+    float exposure;  // in stops
+    uint tonemap;
+    // CameraConstants in camera.rs, prefixed since FragmentConfig already has an offset
+    vec2 camera_scale;
+    vec2 camera_offset;
 }
 pushc;
 
@@ -23,12 +47,43 @@ float saturate(float x) {
 
 #include <consts.glsl>
 #include <rand.glsl>
+#include <tonemap.glsl>
+
+float apply_tonemap(float f) {
+    vec3 color = vec3(f);
+
+    if (pushc.tonemap == TONEMAP_REINHARD) {
+        color = tonemap_reinhard(color);
+    } else if (pushc.tonemap == TONEMAP_ACES) {
+        color = tonemap_aces(color);
+    } else if (pushc.tonemap == TONEMAP_AGX) {
+        color = tonemap_agx(color);
+    }
+
+    return color.x;
+}
 
 void main() {
     // uvec4 pixel = texelFetch(input_tex, ivec2(uv * vec2(pushc.size)), 0);
 
-    float f = texture(sampler2D(input_tex, input_smp), uv).x;
-    // f = saturate(f);
+    vec2 tex_uv = uv * pushc.camera_scale + pushc.camera_offset;
+
+    if (any(lessThan(tex_uv, vec2(0.0))) || any(greaterThan(tex_uv, vec2(1.0)))) {
+        f_color = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    float f = texture(sampler2D(input_tex, input_smp), tex_uv).x;
+    f = max(f, 0.0);
+
+    f *= exposure.scale * exp2(pushc.exposure);
+    f = apply_tonemap(f);
+
+    if (pushc.mapping == MAPPING_LOG) {
+        f = log(1.0 + f * pushc.log_scale) / log(1.0 + pushc.log_scale);
+    } else if (pushc.mapping == MAPPING_GAMMA) {
+        f = pow(f, pushc.gamma);
+    }
 
     f += pushc.offset;
     if (f > 1.0 + EPSILON) {
@@ -39,7 +94,7 @@ void main() {
         f = 1.0 - f;
     }
 
-    vec3 color = mix(pushc.background, pushc.foreground, f);
+    vec3 color = texture(sampler1D(lut_tex, lut_smp), saturate(f)).rgb;
 
     f_color = vec4(color, 1.0);
 }
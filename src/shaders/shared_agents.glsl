@@ -4,6 +4,7 @@
 struct Agent {
     vec2 pos;
     float angle;
+    float energy;  // dead at 0, only changes with lifecycle.enabled
 };
 
 #endif
@@ -0,0 +1,28 @@
+/*
+
+ vec3 srgb_to_linear(vec3 color);
+ vec3 linear_to_srgb(vec3 color);
+
+*/
+
+#ifndef STD_COLOR
+#define STD_COLOR
+
+// https://en.wikipedia.org/wiki/SRGB#Transformation
+
+vec3 srgb_to_linear(vec3 color) {
+    bvec3 cutoff = lessThanEqual(color, vec3(0.04045));
+    vec3 low = color / 12.92;
+    vec3 high = pow((color + 0.055) / 1.055, vec3(2.4));
+    return mix(high, low, cutoff);
+}
+
+vec3 linear_to_srgb(vec3 color) {
+    color = max(color, 0.0);
+    bvec3 cutoff = lessThanEqual(color, vec3(0.0031308));
+    vec3 low = color * 12.92;
+    vec3 high = 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055;
+    return mix(high, low, cutoff);
+}
+
+#endif
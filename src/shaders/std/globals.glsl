@@ -0,0 +1,16 @@
+#ifndef STD_GLOBALS
+#define STD_GLOBALS
+
+// wgpu/globals.rs, every pipeline binds it as the set after its own bind groups
+// define GLOBALS_SET before including this if a pipeline has more than one
+#ifndef GLOBALS_SET
+#define GLOBALS_SET 1
+#endif
+
+layout(set = GLOBALS_SET, binding = 0, std140) uniform Globals {
+    uint seed;
+    uint frame;
+}
+globals;
+
+#endif
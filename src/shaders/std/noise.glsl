@@ -0,0 +1,372 @@
+/*
+
+float value_noise (vecN p);  // N = 2, 3, 4
+float gradient_noise (vecN p);  // perlin
+float simplex_noise (vecN p);
+ vec2 worley_noise (vecN p);  // N = 2, 3, distances to the nearest and second nearest feature point
+ vecN curl_noise (vecN p);  // N = 2, 3, divergence free
+
+float fbm_value (vecN p, int octaves, float lacunarity, float gain);
+float fbm_gradient (vecN p, int octaves, float lacunarity, float gain);
+float fbm_simplex (vecN p, int octaves, float lacunarity, float gain);
+
+the scalar noises are roughly -1 to 1 with a period of 1 between lattice points
+hashed with pcg4d() from rand.glsl and globals.seed, but not the frame, so they don't change over time
+
+*/
+
+#ifndef STD_NOISE
+#define STD_NOISE
+
+#include <consts.glsl>
+#include <rand.glsl>
+
+// hashing
+
+uvec4 noise_key() {
+    return pcg4d(uvec4(globals.seed, uint_MAX, 0u, 0u));
+}
+
+uvec4 noise_hash(ivec4 cell) {
+    return pcg4d(uvec4(cell) ^ noise_key());
+}
+
+// 0 to 1
+vec4 noise_hashf(ivec4 cell) {
+    return to_unit(noise_hash(cell));
+}
+
+// random unit vectors per lattice point
+
+vec2 noise_gradient(ivec2 cell) {
+    float angle = TAU * noise_hashf(ivec4(cell, 0, 0)).x;
+    return vec2(cos(angle), sin(angle));
+}
+
+vec3 noise_gradient(ivec3 cell) {
+    vec2 u = noise_hashf(ivec4(cell, 0)).xy;
+    float z = 1.0 - 2.0 * u.x;
+    float radius = sqrt(max(0.0, 1.0 - z * z));
+    float angle = TAU * u.y;
+    return vec3(radius * vec2(cos(angle), sin(angle)), z);
+}
+
+vec4 noise_gradient(ivec4 cell) {
+    vec4 g = noise_hashf(cell) * 2.0 - 1.0;
+    return g * inversesqrt(max(dot(g, g), EPSILON));
+}
+
+// quintic, so that the second derivative is continuous across cells
+vec2 noise_fade(vec2 t) {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+vec3 noise_fade(vec3 t) {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+vec4 noise_fade(vec4 t) {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+// value noise, interpolated random values at the lattice points
+// the corner loops go over every combination of 0 and 1 offsets
+
+float value_noise(vec2 p) {
+    ivec2 cell = ivec2(floor(p));
+    vec2 u = noise_fade(fract(p));
+
+    float result = 0.0;
+    for (int c = 0; c < 4; c++) {
+        ivec2 o = ivec2(c & 1, c >> 1);
+        vec2 w = mix(1.0 - u, u, vec2(o));
+        result += w.x * w.y * noise_hashf(ivec4(cell + o, 0, 0)).x;
+    }
+
+    return result * 2.0 - 1.0;
+}
+
+float value_noise(vec3 p) {
+    ivec3 cell = ivec3(floor(p));
+    vec3 u = noise_fade(fract(p));
+
+    float result = 0.0;
+    for (int c = 0; c < 8; c++) {
+        ivec3 o = ivec3(c & 1, (c >> 1) & 1, c >> 2);
+        vec3 w = mix(1.0 - u, u, vec3(o));
+        result += w.x * w.y * w.z * noise_hashf(ivec4(cell + o, 0)).x;
+    }
+
+    return result * 2.0 - 1.0;
+}
+
+float value_noise(vec4 p) {
+    ivec4 cell = ivec4(floor(p));
+    vec4 u = noise_fade(fract(p));
+
+    float result = 0.0;
+    for (int c = 0; c < 16; c++) {
+        ivec4 o = ivec4(c & 1, (c >> 1) & 1, (c >> 2) & 1, c >> 3);
+        vec4 w = mix(1.0 - u, u, vec4(o));
+        result += w.x * w.y * w.z * w.w * noise_hashf(cell + o).x;
+    }
+
+    return result * 2.0 - 1.0;
+}
+
+// gradient (perlin) noise, scaled by 2 / sqrt(N) since that is the largest value with unit gradients
+
+float gradient_noise(vec2 p) {
+    ivec2 cell = ivec2(floor(p));
+    vec2 f = fract(p);
+    vec2 u = noise_fade(f);
+
+    float result = 0.0;
+    for (int c = 0; c < 4; c++) {
+        ivec2 o = ivec2(c & 1, c >> 1);
+        vec2 w = mix(1.0 - u, u, vec2(o));
+        result += w.x * w.y * dot(noise_gradient(cell + o), f - vec2(o));
+    }
+
+    return result * 1.41421356;
+}
+
+float gradient_noise(vec3 p) {
+    ivec3 cell = ivec3(floor(p));
+    vec3 f = fract(p);
+    vec3 u = noise_fade(f);
+
+    float result = 0.0;
+    for (int c = 0; c < 8; c++) {
+        ivec3 o = ivec3(c & 1, (c >> 1) & 1, c >> 2);
+        vec3 w = mix(1.0 - u, u, vec3(o));
+        result += w.x * w.y * w.z * dot(noise_gradient(cell + o), f - vec3(o));
+    }
+
+    return result * 1.15470054;
+}
+
+float gradient_noise(vec4 p) {
+    ivec4 cell = ivec4(floor(p));
+    vec4 f = fract(p);
+    vec4 u = noise_fade(f);
+
+    float result = 0.0;
+    for (int c = 0; c < 16; c++) {
+        ivec4 o = ivec4(c & 1, (c >> 1) & 1, (c >> 2) & 1, c >> 3);
+        vec4 w = mix(1.0 - u, u, vec4(o));
+        result += w.x * w.y * w.z * w.w * dot(noise_gradient(cell + o), f - vec4(o));
+    }
+
+    return result;
+}
+
+// simplex noise, after stefan gustavson's "simplex noise demystified"
+// each corner contributes (r^2 - d^2)^4 * dot(gradient, offset)
+
+float simplex_corner(vec2 x, ivec2 cell) {
+    float t = max(0.5 - dot(x, x), 0.0);
+    t *= t;
+    return t * t * dot(noise_gradient(cell), x);
+}
+
+float simplex_corner(vec3 x, ivec3 cell) {
+    float t = max(0.6 - dot(x, x), 0.0);
+    t *= t;
+    return t * t * dot(noise_gradient(cell), x);
+}
+
+float simplex_corner(vec4 x, ivec4 cell) {
+    float t = max(0.6 - dot(x, x), 0.0);
+    t *= t;
+    return t * t * dot(noise_gradient(cell), x);
+}
+
+float simplex_noise(vec2 p) {
+    const float F2 = 0.366025403784;  // (sqrt(3) - 1) / 2
+    const float G2 = 0.211324865405;  // (3 - sqrt(3)) / 6
+
+    vec2 s = floor(p + dot(p, vec2(F2)));
+    vec2 x0 = p - s + dot(s, vec2(G2));
+
+    // which of the two triangles of the skewed cell
+    vec2 o = x0.x > x0.y ? vec2(1.0, 0.0) : vec2(0.0, 1.0);
+
+    vec2 x1 = x0 - o + G2;
+    vec2 x2 = x0 - 1.0 + 2.0 * G2;
+
+    ivec2 cell = ivec2(s);
+    float result = simplex_corner(x0, cell) + simplex_corner(x1, cell + ivec2(o)) +
+                   simplex_corner(x2, cell + 1);
+
+    return 70.0 * result;
+}
+
+float simplex_noise(vec3 p) {
+    const float F3 = 1.0 / 3.0;
+    const float G3 = 1.0 / 6.0;
+
+    vec3 s = floor(p + dot(p, vec3(F3)));
+    vec3 x0 = p - s + dot(s, vec3(G3));
+
+    // which of the six tetrahedra, from the order of the components
+    vec3 g = step(x0.yzx, x0.xyz);
+    vec3 l = 1.0 - g;
+    vec3 o1 = min(g, l.zxy);
+    vec3 o2 = max(g, l.zxy);
+
+    vec3 x1 = x0 - o1 + G3;
+    vec3 x2 = x0 - o2 + 2.0 * G3;
+    vec3 x3 = x0 - 1.0 + 3.0 * G3;
+
+    ivec3 cell = ivec3(s);
+    float result = simplex_corner(x0, cell) + simplex_corner(x1, cell + ivec3(o1)) +
+                   simplex_corner(x2, cell + ivec3(o2)) + simplex_corner(x3, cell + 1);
+
+    return 32.0 * result;
+}
+
+float simplex_noise(vec4 p) {
+    const float F4 = 0.309016994375;  // (sqrt(5) - 1) / 4
+    const float G4 = 0.138196601125;  // (5 - sqrt(5)) / 20
+
+    vec4 s = floor(p + dot(p, vec4(F4)));
+    vec4 x0 = p - s + dot(s, vec4(G4));
+
+    // rank of each component, the largest is traversed first
+    vec4 rank = vec4(0.0);
+    vec3 is_x = step(x0.yzw, x0.xxx);
+    vec3 is_yz = step(x0.zww, x0.yyz);
+    rank.x = is_x.x + is_x.y + is_x.z;
+    rank.yzw = 1.0 - is_x;
+    rank.y += is_yz.x + is_yz.y;
+    rank.zw += 1.0 - is_yz.xy;
+    rank.z += is_yz.z;
+    rank.w += 1.0 - is_yz.z;
+
+    vec4 o1 = clamp(rank - 2.0, 0.0, 1.0);
+    vec4 o2 = clamp(rank - 1.0, 0.0, 1.0);
+    vec4 o3 = clamp(rank, 0.0, 1.0);
+
+    vec4 x1 = x0 - o1 + G4;
+    vec4 x2 = x0 - o2 + 2.0 * G4;
+    vec4 x3 = x0 - o3 + 3.0 * G4;
+    vec4 x4 = x0 - 1.0 + 4.0 * G4;
+
+    ivec4 cell = ivec4(s);
+    float result = simplex_corner(x0, cell) + simplex_corner(x1, cell + ivec4(o1)) +
+                   simplex_corner(x2, cell + ivec4(o2)) + simplex_corner(x3, cell + ivec4(o3)) +
+                   simplex_corner(x4, cell + 1);
+
+    return 27.0 * result;
+}
+
+// worley (cellular) noise, one feature point per cell
+
+vec2 worley_noise(vec2 p) {
+    ivec2 cell = ivec2(floor(p));
+    vec2 f = fract(p);
+
+    vec2 nearest = vec2(1e9);
+    for (int y = -1; y <= 1; y++) {
+        for (int x = -1; x <= 1; x++) {
+            ivec2 o = ivec2(x, y);
+            vec2 point = vec2(o) + noise_hashf(ivec4(cell + o, 0, 0)).xy;
+            float dist = distance(point, f);
+
+            if (dist < nearest.x) {
+                nearest = vec2(dist, nearest.x);
+            } else if (dist < nearest.y) {
+                nearest.y = dist;
+            }
+        }
+    }
+
+    return nearest;
+}
+
+vec2 worley_noise(vec3 p) {
+    ivec3 cell = ivec3(floor(p));
+    vec3 f = fract(p);
+
+    vec2 nearest = vec2(1e9);
+    for (int z = -1; z <= 1; z++) {
+        for (int y = -1; y <= 1; y++) {
+            for (int x = -1; x <= 1; x++) {
+                ivec3 o = ivec3(x, y, z);
+                vec3 point = vec3(o) + noise_hashf(ivec4(cell + o, 0)).xyz;
+                float dist = distance(point, f);
+
+                if (dist < nearest.x) {
+                    nearest = vec2(dist, nearest.x);
+                } else if (dist < nearest.y) {
+                    nearest.y = dist;
+                }
+            }
+        }
+    }
+
+    return nearest;
+}
+
+// curl noise, the curl of simplex noise potentials by central differences
+// divergence free, so particles following it don't bunch up
+
+const float CURL_EPSILON = 0.001;
+
+vec2 curl_noise(vec2 p) {
+    vec2 e = vec2(CURL_EPSILON, 0.0);
+
+    float dx = simplex_noise(p + e.xy) - simplex_noise(p - e.xy);
+    float dy = simplex_noise(p + e.yx) - simplex_noise(p - e.yx);
+
+    return vec2(dy, -dx) / (2.0 * CURL_EPSILON);
+}
+
+// three decorrelated components from offset inputs
+vec3 curl_potential(vec3 p) {
+    return vec3(simplex_noise(p), simplex_noise(p + vec3(31.416, -47.853, 12.793)),
+                simplex_noise(p + vec3(-233.145, -113.408, -185.31)));
+}
+
+vec3 curl_noise(vec3 p) {
+    vec2 e = vec2(CURL_EPSILON, 0.0);
+
+    vec3 dx = curl_potential(p + e.xyy) - curl_potential(p - e.xyy);
+    vec3 dy = curl_potential(p + e.yxy) - curl_potential(p - e.yxy);
+    vec3 dz = curl_potential(p + e.yyx) - curl_potential(p - e.yyx);
+
+    return vec3(dy.z - dz.y, dz.x - dx.z, dx.y - dy.x) / (2.0 * CURL_EPSILON);
+}
+
+// fractal brownian motion, octaves of a noise with increasing frequency and decreasing amplitude
+// normalized by the total amplitude, so it stays roughly -1 to 1
+// lacunarity is usually 2, gain 0.5
+
+#define DEFINE_FBM(name, noise, type)                                      \
+    float name(type p, int octaves, float lacunarity, float gain) {        \
+        float result = 0.0;                                                \
+        float amplitude = 1.0;                                             \
+        float total = 0.0;                                                 \
+        for (int octave = 0; octave < octaves; octave++) {                 \
+            result += amplitude * noise(p);                                \
+            total += amplitude;                                            \
+            p *= lacunarity;                                               \
+            amplitude *= gain;                                             \
+        }                                                                  \
+        return result / max(total, EPSILON);                               \
+    }
+
+DEFINE_FBM(fbm_value, value_noise, vec2)
+DEFINE_FBM(fbm_value, value_noise, vec3)
+DEFINE_FBM(fbm_value, value_noise, vec4)
+DEFINE_FBM(fbm_gradient, gradient_noise, vec2)
+DEFINE_FBM(fbm_gradient, gradient_noise, vec3)
+DEFINE_FBM(fbm_gradient, gradient_noise, vec4)
+DEFINE_FBM(fbm_simplex, simplex_noise, vec2)
+DEFINE_FBM(fbm_simplex, simplex_noise, vec3)
+DEFINE_FBM(fbm_simplex, simplex_noise, vec4)
+
+#undef DEFINE_FBM
+
+#endif
@@ -2,7 +2,16 @@
 
 uvec4 rand (uvecN v);
 
- vec4 randf( vecN v);
+ vec4 randf( vecN v);  // 0 to 1, for any input range
+
+ vec4 rand_uniform (uvec4 v);  // 0 to 1
+ vec4 rand_gaussian (uvec4 v);
+ vec2 rand_unit_vec2 (uvec4 v);
+ vec3 rand_unit_vec3 (uvec4 v);
+ vec2 rand_disk (uvec4 v);
+
+all of these mix in the seed and frame from globals.glsl, use pcg4d() directly for the same numbers every frame
+matches src/random.rs
 
 */
 
@@ -10,6 +19,7 @@ uvec4 rand (uvecN v);
 #define STD_RAND
 
 #include <consts.glsl>
+#include <globals.glsl>
 
 // http://www.jcgt.org/published/0009/03/02/
 uvec4 pcg4d(uvec4 v) {
@@ -30,8 +40,12 @@ uvec4 pcg4d(uvec4 v) {
     return v;
 }
 
+uvec4 rand_key() {
+    return pcg4d(uvec4(globals.seed, globals.frame, 0u, 0u));
+}
+
 uvec4 rand(uvec4 v) {
-    return pcg4d(v);
+    return pcg4d(v ^ rand_key());
 }
 
 uvec4 rand(uvec3 v) {
@@ -46,10 +60,15 @@ uvec4 rand(uint v) {
     return rand(uvec4(v, 0, 0, 0));
 }
 
-// randf best for 0.0 - 1.0  // todo fix
+// 0 to 1 exclusive, from the top 24 bits so that every value is exactly representable
+vec4 to_unit(uvec4 v) {
+    return vec4(v >> 8u) * (1.0 / 16777216.0);
+}
+
+// the bits of the input are hashed, so any range works and neighbouring floats don't collide
 
 vec4 randf(vec4 v) {
-    return rand(uvec4(v * uint_MAXf)) / uint_MAXf;
+    return to_unit(rand(floatBitsToUint(v)));
 }
 
 vec4 randf(vec3 v) {
@@ -64,4 +83,64 @@ vec4 randf(float v) {
     return randf(vec4(v, 0, 0, 0));
 }
 
+// distributions, each takes the same input as rand()
+
+vec4 rand_uniform(uvec4 v) {
+    return to_unit(rand(v));
+}
+
+vec4 rand_uniform(uint v) {
+    return rand_uniform(uvec4(v, 0, 0, 0));
+}
+
+// box-muller, 1 - u.x is in (0, 1] so the log is finite
+vec2 box_muller(vec2 u) {
+    float radius = sqrt(-2.0 * log(1.0 - u.x));
+    float angle = TAU * u.y;
+    return radius * vec2(cos(angle), sin(angle));
+}
+
+// four independent samples with mean 0 and standard deviation 1
+vec4 rand_gaussian(uvec4 v) {
+    vec4 u = rand_uniform(v);
+    return vec4(box_muller(u.xy), box_muller(u.zw));
+}
+
+vec4 rand_gaussian(uint v) {
+    return rand_gaussian(uvec4(v, 0, 0, 0));
+}
+
+vec2 rand_unit_vec2(uvec4 v) {
+    float angle = TAU * rand_uniform(v).x;
+    return vec2(cos(angle), sin(angle));
+}
+
+vec2 rand_unit_vec2(uint v) {
+    return rand_unit_vec2(uvec4(v, 0, 0, 0));
+}
+
+// uniform on the sphere: z is uniform in [-1, 1]
+vec3 rand_unit_vec3(uvec4 v) {
+    vec2 u = rand_uniform(v).xy;
+    float z = 1.0 - 2.0 * u.x;
+    float radius = sqrt(max(0.0, 1.0 - z * z));
+    float angle = TAU * u.y;
+    return vec3(radius * vec2(cos(angle), sin(angle)), z);
+}
+
+vec3 rand_unit_vec3(uint v) {
+    return rand_unit_vec3(uvec4(v, 0, 0, 0));
+}
+
+// uniform in the unit disk
+vec2 rand_disk(uvec4 v) {
+    vec2 u = rand_uniform(v).xy;
+    float angle = TAU * u.y;
+    return sqrt(u.x) * vec2(cos(angle), sin(angle));
+}
+
+vec2 rand_disk(uint v) {
+    return rand_disk(uvec4(v, 0, 0, 0));
+}
+
 #endif
@@ -0,0 +1,59 @@
+/*
+
+ vec3 tonemap_reinhard(vec3 color);
+ vec3 tonemap_aces(vec3 color);
+ vec3 tonemap_agx(vec3 color);
+
+ all take linear hdr colors and return linear colors in 0.0 - 1.0
+
+*/
+
+#ifndef STD_TONEMAP
+#define STD_TONEMAP
+
+vec3 tonemap_reinhard(vec3 color) {
+    return color / (1.0 + color);
+}
+
+// https://knarkowicz.wordpress.com/2016/01/06/aces-filmic-tone-mapping-curve/
+vec3 tonemap_aces(vec3 color) {
+    const float a = 2.51;
+    const float b = 0.03;
+    const float c = 2.43;
+    const float d = 0.59;
+    const float e = 0.14;
+
+    return clamp((color * (a * color + b)) / (color * (c * color + d) + e), 0.0, 1.0);
+}
+
+// https://iolite-engine.com/blog_posts/minimal_agx_implementation
+vec3 agx_contrast_approx(vec3 x) {
+    vec3 x2 = x * x;
+    vec3 x4 = x2 * x2;
+
+    return 15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 +
+           0.1191 * x - 0.00232;
+}
+
+vec3 tonemap_agx(vec3 color) {
+    const mat3 agx_mat = mat3(0.842479062253094, 0.0423282422610123, 0.0423756549057051,
+                              0.0784335999999992, 0.878468636469772, 0.0784336,
+                              0.0792237451477643, 0.0791661274605434, 0.879142973793104);
+    const mat3 agx_mat_inv = mat3(1.19687900512017, -0.0528968517574562, -0.0529716355144438,
+                                  -0.0980208811401368, 1.15190312990417, -0.0980434501171241,
+                                  -0.0990297440797205, -0.0989611768448433, 1.15107367264116);
+
+    const float min_ev = -12.47393;
+    const float max_ev = 4.026069;
+
+    color = agx_mat * color;
+    color = clamp(log2(max(color, 1e-10)), min_ev, max_ev);
+    color = (color - min_ev) / (max_ev - min_ev);
+    color = agx_contrast_approx(color);
+    color = agx_mat_inv * color;
+
+    // the curve outputs display encoded values, undo that to stay linear
+    return pow(clamp(color, 0.0, 1.0), vec3(2.2));
+}
+
+#endif
@@ -43,6 +43,20 @@ impl TextureDesc {
         }
     }
 
+    // height is ignored
+    pub fn into_1d(&self, usage: TextureUsage) -> TextureDescriptor<'static> {
+        let base = self.into_2d(usage);
+
+        TextureDescriptor {
+            size: Extent3d {
+                height: 1,
+                ..base.size
+            },
+            dimension: TextureDimension::D1,
+            ..base
+        }
+    }
+
     pub fn into_2d_array(&self, usage: TextureUsage, length: u32) -> TextureDescriptor<'static> {
         let base = self.into_2d(usage);
 
@@ -166,8 +180,15 @@ pub fn to_image(data: &[u8], desc: &TextureDesc) -> image::ImageResult<image::Dy
         }
 
         fn color_type(&self) -> image::ColorType {
+            // the png is tagged as srgb, srgb formats already store encoded values
+            // while the linear formats were written with the encoding done in the shader
             match self.desc.format {
-                TextureFormat::Bgra8Unorm => image::ColorType::Bgra8,
+                TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+                    image::ColorType::Bgra8
+                }
+                TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+                    image::ColorType::Rgba8
+                }
                 _ => unimplemented!(),
             }
         }
@@ -251,6 +272,20 @@ pub fn group_size(x: u32, a: u32) -> u32 {
     (x + a - 1) / a
 }
 
+// imgui color edits are srgb, everything before the final render pass is linear
+// same as srgb_to_linear in color.glsl
+pub fn srgb_to_linear(color: [f32; 3]) -> [f32; 3] {
+    let channel = |c: f32| {
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+
+    [channel(color[0]), channel(color[1]), channel(color[2])]
+}
+
 pub fn init_log() {
     use std::env;
 
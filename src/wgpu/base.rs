@@ -2,24 +2,69 @@ use std::iter;
 
 use pollster::FutureExt as _;
 use wgpu::{
-    Adapter, BackendBit, CommandEncoder, Device, DeviceDescriptor, Features, Instance, Limits,
-    PowerPreference, Queue, RenderPass, RenderPassColorAttachment, RenderPassDescriptor,
-    RequestAdapterOptions, Surface, TextureView,
+    Adapter, BackendBit, Buffer, Color, CommandEncoder, Device, DeviceDescriptor, Features,
+    Instance, Limits, LoadOp, Operations, PowerPreference, Queue, RenderPass,
+    RenderPassColorAttachment, RenderPassDescriptor, RequestAdapterOptions, Surface, TextureView,
 };
 
+use crate::shaders::ShaderError;
 use crate::util::SafeWgpuSurface;
 
-// simple render pass that only clears the frame to black. ignore if using depth buffer, not clearing frame, or anything more complex
-fn begin_render_pass<'a>(
+use super::globals::{globals_buffer, Globals};
+
+pub struct ColorAttachment<'a> {
+    pub view: &'a TextureView,
+    pub ops: Operations<Color>,
+}
+
+impl<'a> ColorAttachment<'a> {
+    // same as wgpu's default ops: clear to black and store
+    pub fn new(view: &'a TextureView) -> Self {
+        Self {
+            view,
+            ops: Default::default(),
+        }
+    }
+
+    pub fn clear(view: &'a TextureView, color: Color) -> Self {
+        Self {
+            view,
+            ops: Operations {
+                load: LoadOp::Clear(color),
+                store: true,
+            },
+        }
+    }
+
+    // keep the previous contents, eg when drawing on top of an earlier pass
+    pub fn load(view: &'a TextureView) -> Self {
+        Self {
+            view,
+            ops: Operations {
+                load: LoadOp::Load,
+                store: true,
+            },
+        }
+    }
+}
+
+// render pass with only color attachments. ignore if using depth buffer or msaa
+// can be called from render_encoder() to render into intermediate textures before the final pass
+pub fn begin_render_pass<'a>(
     encoder: &'a mut CommandEncoder,
-    texture: &'a TextureView,
+    attachments: &[ColorAttachment<'a>],
 ) -> RenderPass<'a> {
-    encoder.begin_render_pass(&RenderPassDescriptor {
-        color_attachments: &[RenderPassColorAttachment {
-            view: texture,
+    let color_attachments: Vec<_> = attachments
+        .iter()
+        .map(|attachment| RenderPassColorAttachment {
+            view: attachment.view,
             resolve_target: None,
-            ops: Default::default(),
-        }],
+            ops: attachment.ops,
+        })
+        .collect();
+
+    encoder.begin_render_pass(&RenderPassDescriptor {
+        color_attachments: &color_attachments,
         ..Default::default()
     })
 }
@@ -45,7 +90,10 @@ pub struct WgpuBase {
     pub adapter: Adapter,
     pub device: Device,
     pub queue: Queue,
+    pub globals: Globals,
+    pub(super) globals_buffer: Buffer,
     pub(super) shaders: super::shaders::Shaders,
+    pub(super) shader_errors: Vec<ShaderError>,
 }
 
 impl WgpuBase {
@@ -69,13 +117,21 @@ impl WgpuBase {
         dbg!(device.features());
         // device.on_uncaptured_error(|err| eprintln!("{:#?}", err));
 
-        Self {
+        let globals_buffer = globals_buffer(&device);
+
+        let mut this = Self {
             instance,
             adapter,
             device,
             queue,
+            globals: Default::default(),
+            globals_buffer,
             shaders: Default::default(),
-        }
+            shader_errors: Vec::new(),
+        };
+
+        this.preload_fallbacks();
+        this
     }
 
     pub fn new() -> Self {
@@ -100,7 +156,11 @@ impl WgpuBase {
         target.render_encoder(self, &mut encoder, false);
 
         {
-            let mut render_pass = begin_render_pass(&mut encoder, texture);
+            let attachment = ColorAttachment {
+                view: texture,
+                ops: target.ops(),
+            };
+            let mut render_pass = begin_render_pass(&mut encoder, &[attachment]);
             target.render(self, &mut render_pass);
         }
 
@@ -114,4 +174,9 @@ impl WgpuBase {
 pub trait WgpuBaseRender {
     fn render<'a>(&'a mut self, wgpu_base: &WgpuBase, render_pass: &mut RenderPass<'a>);
     fn render_encoder(&mut self, wgpu_base: &WgpuBase, encoder: &mut CommandEncoder, after: bool);
+
+    // load/store ops of the final pass onto the output texture
+    fn ops(&self) -> Operations<Color> {
+        Default::default()
+    }
 }
@@ -1,7 +1,7 @@
 use wgpu::{
     BindGroup, BindGroupDescriptor, BindGroupLayout, BindGroupLayoutDescriptor,
     BindGroupLayoutEntry, BindingResource, BindingType, Buffer, BufferBindingType, Device, Sampler,
-    ShaderStage, StorageTextureAccess, TextureDescriptor, TextureView,
+    ShaderStage, StorageTextureAccess, TextureDescriptor, TextureSampleType, TextureView,
 };
 
 use crate::util::{texture_view_dimension, SamplerDesc};
@@ -18,7 +18,7 @@ impl WgpuBase {
             .map(|(index, entry)| BindGroupLayoutEntry {
                 binding: index as _,
                 visibility: ShaderStage::all(),
-                ty: entry.as_layout(),
+                ty: entry.as_layout(entries.get(index + 1)),
                 count: None,
             })
             .collect();
@@ -77,7 +77,9 @@ pub enum BindGroupEntry<'a> {
 }
 
 impl<'a> BindGroupEntry<'a> {
-    fn as_layout(&self) -> BindingType {
+    // a float texture followed by a filtering sampler is declared filterable,
+    // which formats like r32float only are with TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+    fn as_layout(&self, next: Option<&Self>) -> BindingType {
         match self {
             Self::Buffer { ty, .. } => BindingType::Buffer {
                 ty: *ty,
@@ -95,7 +97,15 @@ impl<'a> BindGroupEntry<'a> {
                 desc,
                 ..
             } => BindingType::Texture {
-                sample_type: desc.format.describe().sample_type,
+                sample_type: match (desc.format.describe().sample_type, next) {
+                    (
+                        TextureSampleType::Float { .. },
+                        Some(Self::Sampler {
+                            desc: SamplerDesc { filter: true, .. },
+                        }),
+                    ) => TextureSampleType::Float { filterable: true },
+                    (sample_type, _) => sample_type,
+                },
                 view_dimension: texture_view_dimension(desc),
                 multisampled: false,
             },
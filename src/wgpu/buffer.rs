@@ -1,6 +1,16 @@
+use std::future::Future;
+use std::iter;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::ptr;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use bytemuck::Pod;
+use pollster::FutureExt as _;
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    Buffer, BufferDescriptor, BufferUsage,
+    Buffer, BufferAddress, BufferAsyncError, BufferDescriptor, BufferUsage, CommandEncoder,
+    Maintain, MapMode,
 };
 
 use crate::util::InitType;
@@ -28,6 +38,152 @@ impl WgpuBase {
             },
         )
     }
+
+    // copy len elements starting at offset bytes into a vec, buffer needs BufferUsage::COPY_SRC
+    // this blocks until all previously submitted work is done, so avoid doing it every frame
+    pub fn readback<T: Pod>(&self, buffer: &Buffer, offset: BufferAddress, len: usize) -> Vec<T> {
+        let size = (len * std::mem::size_of::<T>()) as BufferAddress;
+
+        let staging = self.device.create_buffer(&BufferDescriptor {
+            label: None,
+            size,
+            usage: BufferUsage::COPY_DST | BufferUsage::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        encoder.copy_buffer_to_buffer(buffer, offset, &staging, 0, size);
+        self.queue.submit(iter::once(encoder.finish()));
+
+        let slice = staging.slice(..);
+        let mapping = slice.map_async(MapMode::Read);
+        self.device.poll(Maintain::Wait);
+        mapping.block_on().unwrap();
+
+        let view = slice.get_mapped_range();
+        let data = bytemuck::cast_slice(&view).to_vec();
+        drop(view);
+
+        data
+    }
+
+    // like readback(), but without waiting for the gpu, see AsyncReadback
+    pub fn async_readback<T: Pod>(&self, len: usize) -> AsyncReadback<T> {
+        let size = (len * std::mem::size_of::<T>()) as BufferAddress;
+
+        let staging = (0..AsyncReadback::<T>::FRAMES)
+            .map(|_| Staging {
+                buffer: self.device.create_buffer(&BufferDescriptor {
+                    label: None,
+                    size,
+                    usage: BufferUsage::COPY_DST | BufferUsage::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                state: StagingState::Free,
+                copy: 0,
+            })
+            .collect();
+
+        AsyncReadback {
+            size,
+            staging,
+            copies: 0,
+            phantom: PhantomData,
+        }
+    }
+}
+
+type MapFuture = Pin<Box<dyn Future<Output = Result<(), BufferAsyncError>> + Send>>;
+
+enum StagingState {
+    Free,
+    Copied, // in an encoder, can only be mapped once that was submitted
+    Mapping(MapFuture),
+}
+
+struct Staging {
+    buffer: Buffer,
+    state: StagingState,
+    copy: u64, // AsyncReadback::copies when it was copied into
+}
+
+// copy() a buffer every frame and read() whatever arrived since, usually a frame or two later
+// when the gpu falls behind by more than FRAMES, copies are skipped instead of waiting
+pub struct AsyncReadback<T> {
+    size: BufferAddress,
+    staging: Vec<Staging>,
+    copies: u64,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Pod> AsyncReadback<T> {
+    const FRAMES: usize = 3;
+
+    // buffer needs BufferUsage::COPY_SRC, offset is in bytes
+    pub fn copy(&mut self, encoder: &mut CommandEncoder, buffer: &Buffer, offset: BufferAddress) {
+        let free = self
+            .staging
+            .iter_mut()
+            .find(|staging| matches!(staging.state, StagingState::Free));
+
+        if let Some(staging) = free {
+            encoder.copy_buffer_to_buffer(buffer, offset, &staging.buffer, 0, self.size);
+            staging.state = StagingState::Copied;
+            staging.copy = self.copies;
+            self.copies += 1;
+        }
+    }
+
+    // the newest copy that finished since the last call, call once per frame before copy()
+    // everything copied before this frame was submitted, so it can be mapped now
+    pub fn read(&mut self, wgpu_base: &WgpuBase) -> Option<Vec<T>> {
+        wgpu_base.device.poll(Maintain::Poll);
+
+        let mut newest: Option<(u64, Vec<T>)> = None;
+
+        for staging in &mut self.staging {
+            match &mut staging.state {
+                StagingState::Free => {}
+                StagingState::Copied => {
+                    let mapping = staging.buffer.slice(..).map_async(MapMode::Read);
+                    staging.state = StagingState::Mapping(Box::pin(mapping));
+                }
+                StagingState::Mapping(mapping) => match poll_now(mapping) {
+                    Poll::Pending => {}
+                    Poll::Ready(result) => {
+                        if result.is_ok() {
+                            let newer = newest
+                                .as_ref()
+                                .map_or(true, |(copy, _)| staging.copy > *copy);
+
+                            if newer {
+                                let view = staging.buffer.slice(..).get_mapped_range();
+                                newest = Some((staging.copy, bytemuck::cast_slice(&view).to_vec()));
+                            }
+
+                            staging.buffer.unmap();
+                        }
+
+                        staging.state = StagingState::Free;
+                    }
+                },
+            }
+        }
+
+        newest.map(|(_, data)| data)
+    }
+}
+
+// wgpu completes mappings in device.poll(), so there is nothing to wake
+fn poll_now(future: &mut MapFuture) -> Poll<Result<(), BufferAsyncError>> {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    let waker = unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) };
+    future.as_mut().poll(&mut Context::from_waker(&waker))
 }
 
 pub struct BufferDesc {
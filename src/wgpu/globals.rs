@@ -0,0 +1,36 @@
+use crevice::std140::{AsStd140, Std140};
+use wgpu::{Buffer, BufferBindingType, BufferDescriptor, BufferUsage, Device};
+
+use super::{BindGroupEntry, BindGroupResult, WgpuBase};
+
+// std/globals.glsl, bound to every pipeline as the set after its own bind groups
+#[derive(AsStd140, Clone, Copy, Debug, Default)]
+pub struct Globals {
+    pub seed: u32,  // set by apps in prepare(), mixed into rand() in std/rand.glsl
+    pub frame: u32, // counts up after every WgpuWindowed::render, apps may reset it
+}
+
+pub(super) fn globals_buffer(device: &Device) -> Buffer {
+    device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: Globals::std140_size_static() as _,
+        usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+impl WgpuBase {
+    // uploads self.globals, the frame's command buffers see the values from the last call
+    pub fn write_globals(&self) {
+        self.queue
+            .write_buffer(&self.globals_buffer, 0, self.globals.as_std140().as_bytes());
+    }
+
+    // each pipeline gets its own bind group, all pointing at the same buffer
+    pub(super) fn globals_bind_group(&self) -> BindGroupResult {
+        self.bind_group(&[BindGroupEntry::Buffer {
+            ty: BufferBindingType::Uniform,
+            buffer: &self.globals_buffer,
+        }])
+    }
+}
@@ -0,0 +1,63 @@
+use crevice::std430::{AsStd430, Std430};
+use wgpu::{Buffer, BufferBindingType, BufferUsage, ComputePass};
+
+use crate::util::InitType;
+
+use super::{
+    BindGroupEntry, BufferDesc, ComputePipelineDesc, FullComputePipeline, PipelineExt, WgpuBase,
+};
+
+#[derive(AsStd430)]
+struct ArgsConstants {
+    workgroup_size: u32,
+}
+
+// workgroup counts for ComputePass::dispatch_indirect, computed on the gpu from a count that
+// the gpu writes itself, eg: the number of live agents, so that the cpu never has to read it back
+pub struct IndirectDispatch {
+    pub args: Buffer, // x, y, z workgroups
+    pipeline: FullComputePipeline,
+}
+
+impl WgpuBase {
+    // the count is the first u32 of count_buffer, which needs BufferUsage::STORAGE
+    pub fn indirect_dispatch(&mut self, count_buffer: &Buffer) -> IndirectDispatch {
+        let args = self.buffer(
+            BufferDesc {
+                size: 3 * std::mem::size_of::<u32>(),
+                usage: BufferUsage::STORAGE | BufferUsage::INDIRECT,
+            },
+            InitType::Zeros,
+        );
+
+        let pipeline = self.compute_pipeline(ComputePipelineDesc {
+            bind_groups: vec![self.bind_group(&[
+                BindGroupEntry::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    buffer: count_buffer,
+                },
+                BindGroupEntry::Buffer {
+                    ty: BufferBindingType::Storage { read_only: false },
+                    buffer: &args,
+                },
+            ])],
+            shader: "indirect_args.comp",
+            defines: &[],
+            push_constants: Some(ArgsConstants::std430_size_static() as _),
+        });
+
+        IndirectDispatch { args, pipeline }
+    }
+}
+
+impl IndirectDispatch {
+    // writes the args for one workgroup per target's workgroup size of items
+    // this changes the pipeline, so begin() the target afterwards
+    pub fn encode<'a>(&'a self, compute_pass: &mut ComputePass<'a>, target: &FullComputePipeline) {
+        let [workgroup_size, _, _] = target.workgroup_size();
+
+        compute_pass.begin(&self.pipeline);
+        compute_pass.pushc(ArgsConstants { workgroup_size }.as_std430().as_bytes());
+        compute_pass.dispatch(1, 1, 1);
+    }
+}
@@ -1,16 +1,23 @@
 mod base;
 mod bind_group;
 mod buffer;
+mod globals;
+mod indirect;
 mod pipeline;
+mod reduce;
 mod shaders;
 mod texture;
 mod windowed;
 
-pub use base::{WgpuBase, WgpuBaseRender};
+pub use base::{begin_render_pass, ColorAttachment, WgpuBase, WgpuBaseRender};
 pub use bind_group::{BindGroupEntry, BindGroupResult};
-pub use buffer::BufferDesc;
+pub use buffer::{AsyncReadback, BufferDesc};
+pub use globals::Globals;
+pub use indirect::IndirectDispatch;
 pub use pipeline::{
-    ComputePipelineDesc, FullComputePipeline, FullRenderPipeline, PipelineExt, RenderPipelineDesc,
+    ComputePipelineDesc, DispatchExt, FullComputePipeline, FullRenderPipeline, PipelineExt,
+    RenderPipelineDesc,
 };
+pub use reduce::{Histogram, ReduceOp, ReduceResult, ReduceSource, Reduction};
 pub use texture::TextureResult;
 pub use windowed::{WgpuWindowed, WgpuWindowedRender};
@@ -1,3 +1,5 @@
+use std::iter;
+
 use wgpu::{
     BindGroup, BindGroupLayout, ColorTargetState, ComputePass, ComputePipeline,
     ComputePipelineDescriptor, Face, FragmentState, PipelineLayout, PipelineLayoutDescriptor,
@@ -5,7 +7,9 @@ use wgpu::{
     ShaderStage, VertexState,
 };
 
-use super::{BindGroupResult, WgpuBase};
+use super::{BindGroupResult, IndirectDispatch, WgpuBase};
+use crate::shaders;
+use crate::util::{group_size, TextureDesc};
 
 impl WgpuBase {
     pub fn render_pipeline(&mut self, desc: RenderPipelineDesc) -> FullRenderPipeline {
@@ -15,21 +19,24 @@ impl WgpuBase {
         let vertex_shader = "fullscreen.vert";
         let fragment_shader = desc.shader;
 
-        self.shader_preload(vertex_shader);
-        self.shader_preload(fragment_shader);
+        self.shader_preload(vertex_shader, ShaderStage::VERTEX);
+        self.shader_preload(fragment_shader, ShaderStage::FRAGMENT);
+
+        let vertex = self.shader(vertex_shader);
+        let fragment = self.shader(fragment_shader);
 
         let device = &self.device;
         let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
             layout: Some(&layout),
             vertex: VertexState {
-                module: self.shader(vertex_shader),
-                entry_point: "main",
+                module: &vertex.module,
+                entry_point: &vertex.entry_point,
                 buffers: &[],
             },
             fragment: Some(FragmentState {
-                module: self.shader(fragment_shader),
-                entry_point: "main",
-                targets: &[desc.target],
+                module: &fragment.module,
+                entry_point: &fragment.entry_point,
+                targets: &desc.targets,
             }),
             primitive: PrimitiveState {
                 cull_mode: Some(Face::Back),
@@ -50,19 +57,22 @@ impl WgpuBase {
         let (layout, binds) =
             self.pipeline(desc.bind_groups, desc.push_constants, ShaderStage::COMPUTE);
 
-        self.shader_preload(desc.shader);
+        let shader = shaders::variant_name(desc.shader, desc.defines);
+        self.shader_preload(&shader, ShaderStage::COMPUTE);
+        let shader = self.shader(&shader);
 
         let device = &self.device;
         let pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
             layout: Some(&layout),
-            module: &self.shader(desc.shader),
-            entry_point: "main",
+            module: &shader.module,
+            entry_point: &shader.entry_point,
             label: None,
         });
 
         FullComputePipeline {
             pipeline,
             bind_groups: binds,
+            workgroup_size: shader.workgroup_size,
         }
     }
 
@@ -74,8 +84,10 @@ impl WgpuBase {
     ) -> (PipelineLayout, Vec<BindGroup>) {
         let device = &self.device;
 
+        // std/globals.glsl is the set after the pipeline's own
         let (layouts, binds): (Vec<BindGroupLayout>, Vec<BindGroup>) = bind_groups
             .into_iter()
+            .chain(iter::once(self.globals_bind_group()))
             .map(|res| (res.layout, res.bind))
             .unzip();
 
@@ -102,8 +114,8 @@ impl WgpuBase {
 
 pub struct RenderPipelineDesc {
     pub bind_groups: Vec<BindGroupResult>,
-    pub shader: &'static str,
-    pub target: ColorTargetState,
+    pub shader: &'static str,           // "<file>" or "<file>:<entry>"
+    pub targets: Vec<ColorTargetState>, // one per color attachment of the render pass
     pub push_constants: Option<u32>,
 }
 
@@ -114,13 +126,23 @@ pub struct FullRenderPipeline {
 
 pub struct ComputePipelineDesc {
     pub bind_groups: Vec<BindGroupResult>,
-    pub shader: &'static str,
+    pub shader: &'static str,             // "<file>" or "<file>:<entry>"
+    pub defines: &'static [&'static str], // selects a variant of the shader, eg: &["SPECIES=4"]
     pub push_constants: Option<u32>,
 }
 
 pub struct FullComputePipeline {
     pipeline: ComputePipeline,
     bind_groups: Vec<BindGroup>,
+    workgroup_size: Option<[u32; 3]>,
+}
+
+impl FullComputePipeline {
+    // from the shader, so that it is only written there
+    pub fn workgroup_size(&self) -> [u32; 3] {
+        self.workgroup_size
+            .expect("could not read the workgroup size of the shader")
+    }
 }
 
 pub trait PipelineExt<'a> {
@@ -145,6 +167,30 @@ impl<'a> PipelineExt<'a> for RenderPass<'a> {
     }
 }
 
+// dispatches enough workgroups of the pipeline to cover every item or texel, run begin() first
+pub trait DispatchExt<'a> {
+    fn dispatch_for(&mut self, pipeline: &FullComputePipeline, items: u32);
+    fn dispatch_for_texture(&mut self, pipeline: &FullComputePipeline, desc: &TextureDesc);
+    // with the workgroup counts from IndirectDispatch::encode()
+    fn dispatch_for_indirect(&mut self, indirect: &'a IndirectDispatch);
+}
+
+impl<'a> DispatchExt<'a> for ComputePass<'a> {
+    fn dispatch_for(&mut self, pipeline: &FullComputePipeline, items: u32) {
+        let [x, _, _] = pipeline.workgroup_size();
+        self.dispatch(group_size(items, x), 1, 1);
+    }
+
+    fn dispatch_for_texture(&mut self, pipeline: &FullComputePipeline, desc: &TextureDesc) {
+        let [x, y, _] = pipeline.workgroup_size();
+        self.dispatch(group_size(desc.width, x), group_size(desc.height, y), 1);
+    }
+
+    fn dispatch_for_indirect(&mut self, indirect: &'a IndirectDispatch) {
+        self.dispatch_indirect(&indirect.args, 0);
+    }
+}
+
 impl<'a> PipelineExt<'a> for ComputePass<'a> {
     type FullPipeline = FullComputePipeline;
 
@@ -0,0 +1,302 @@
+use crevice::std430::{AsStd430, Std430};
+use wgpu::{Buffer, BufferBindingType, BufferUsage, CommandEncoder, ComputePass};
+
+use crate::util::{group_size, InitType};
+
+use super::{
+    AsyncReadback, BindGroupEntry, BufferDesc, ComputePipelineDesc, DispatchExt,
+    FullComputePipeline, PipelineExt, TextureResult, WgpuBase,
+};
+
+// stats are computed over a single channel of a float texture, or over a buffer of f32
+#[derive(Clone, Copy)]
+pub enum ReduceSource<'a> {
+    Texture {
+        texture: &'a TextureResult,
+        channel: u32,
+    },
+    Buffer {
+        buffer: &'a Buffer,
+        len: u32,
+    },
+}
+
+impl<'a> ReduceSource<'a> {
+    fn entry(&self) -> BindGroupEntry<'a> {
+        match *self {
+            Self::Texture { texture, .. } => BindGroupEntry::Texture {
+                storage: None,
+                desc: texture.desc.clone(),
+                view: &texture.view,
+            },
+            Self::Buffer { buffer, .. } => BindGroupEntry::Buffer {
+                ty: BufferBindingType::Storage { read_only: true },
+                buffer,
+            },
+        }
+    }
+
+    // the channel or len push constant
+    fn param(&self) -> u32 {
+        match *self {
+            Self::Texture { channel, .. } => channel,
+            Self::Buffer { len, .. } => len,
+        }
+    }
+
+    // workgroups to cover the source, 2d for textures
+    fn groups(&self, workgroup_size: [u32; 3]) -> (u32, u32) {
+        let [x, y, _] = workgroup_size;
+
+        match *self {
+            Self::Texture { texture, .. } => {
+                let size = texture.desc.size;
+                (group_size(size.width, x), group_size(size.height, y))
+            }
+            Self::Buffer { len, .. } => (group_size(len, x), 1),
+        }
+    }
+
+    fn is_texture(&self) -> bool {
+        matches!(self, Self::Texture { .. })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ReduceOp {
+    Sum,
+    Min,
+    Max,
+    Mean,
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ReduceResult {
+    pub sum: f32,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+}
+
+impl ReduceResult {
+    pub fn get(&self, op: ReduceOp) -> f32 {
+        match op {
+            ReduceOp::Sum => self.sum,
+            ReduceOp::Min => self.min,
+            ReduceOp::Max => self.max,
+            ReduceOp::Mean => self.mean,
+        }
+    }
+}
+
+#[derive(AsStd430)]
+struct ParamConstants {
+    param: u32,
+}
+
+#[derive(AsStd430)]
+struct HistogramConstants {
+    low: f32,
+    high: f32,
+    bins: u32,
+    param: u32,
+}
+
+impl WgpuBase {
+    pub fn reduction(&mut self, source: ReduceSource<'_>) -> Reduction {
+        let shader = if source.is_texture() {
+            "reduce/texture.comp"
+        } else {
+            "reduce/buffer.comp"
+        };
+
+        // one partial per workgroup of the first pass
+        let groups = source.groups(self.shader_workgroup_size(shader));
+        let count = groups.0 * groups.1;
+
+        let partials = self.buffer(
+            BufferDesc {
+                size: count as usize * std::mem::size_of::<[f32; 4]>(),
+                usage: BufferUsage::STORAGE,
+            },
+            InitType::Uninit,
+        );
+
+        let output = self.buffer(
+            BufferDesc {
+                size: std::mem::size_of::<[f32; 4]>(),
+                usage: BufferUsage::STORAGE | BufferUsage::COPY_SRC,
+            },
+            InitType::Zeros,
+        );
+
+        let first = self.compute_pipeline(ComputePipelineDesc {
+            bind_groups: vec![self.bind_group(&[
+                source.entry(),
+                BindGroupEntry::Buffer {
+                    ty: BufferBindingType::Storage { read_only: false },
+                    buffer: &partials,
+                },
+            ])],
+            shader,
+            defines: &[],
+            push_constants: Some(ParamConstants::std430_size_static() as _),
+        });
+
+        let last = self.compute_pipeline(ComputePipelineDesc {
+            bind_groups: vec![self.bind_group(&[
+                BindGroupEntry::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    buffer: &partials,
+                },
+                BindGroupEntry::Buffer {
+                    ty: BufferBindingType::Storage { read_only: false },
+                    buffer: &output,
+                },
+            ])],
+            shader: "reduce/final.comp",
+            defines: &[],
+            push_constants: Some(ParamConstants::std430_size_static() as _),
+        });
+
+        Reduction {
+            readback: self.async_readback(4),
+            output,
+            first,
+            last,
+            param: source.param(),
+            groups,
+        }
+    }
+
+    pub fn histogram(&mut self, source: ReduceSource<'_>, bins: u32) -> Histogram {
+        let buffer = self.buffer(
+            BufferDesc {
+                size: bins as usize * std::mem::size_of::<u32>(),
+                usage: BufferUsage::STORAGE | BufferUsage::COPY_SRC,
+            },
+            InitType::Zeros,
+        );
+
+        let clear = self.compute_pipeline(ComputePipelineDesc {
+            bind_groups: vec![self.bind_group(&[BindGroupEntry::Buffer {
+                ty: BufferBindingType::Storage { read_only: false },
+                buffer: &buffer,
+            }])],
+            shader: "reduce/clear.comp",
+            defines: &[],
+            push_constants: Some(ParamConstants::std430_size_static() as _),
+        });
+
+        let pipeline = self.compute_pipeline(ComputePipelineDesc {
+            bind_groups: vec![self.bind_group(&[
+                source.entry(),
+                BindGroupEntry::Buffer {
+                    ty: BufferBindingType::Storage { read_only: false },
+                    buffer: &buffer,
+                },
+            ])],
+            shader: if source.is_texture() {
+                "reduce/histogram_texture.comp"
+            } else {
+                "reduce/histogram_buffer.comp"
+            },
+            defines: &[],
+            push_constants: Some(HistogramConstants::std430_size_static() as _),
+        });
+
+        Histogram {
+            readback: self.async_readback(bins as _),
+            buffer,
+            bins,
+            range: (0.0, 1.0),
+            param: source.param(),
+            groups: source.groups(pipeline.workgroup_size()),
+            clear,
+            pipeline,
+        }
+    }
+}
+
+pub struct Reduction {
+    pub output: Buffer, // std430 vec4(sum, min, max, mean), can be bound by other shaders
+    first: FullComputePipeline,
+    last: FullComputePipeline,
+    param: u32,
+    groups: (u32, u32),
+    readback: AsyncReadback<f32>,
+}
+
+impl Reduction {
+    pub fn encode<'a>(&'a self, compute_pass: &mut ComputePass<'a>) {
+        let (x, y) = self.groups;
+
+        compute_pass.begin(&self.first);
+        compute_pass.pushc(ParamConstants { param: self.param }.as_std430().as_bytes());
+        compute_pass.dispatch(x, y, 1);
+
+        compute_pass.begin(&self.last);
+        compute_pass.pushc(ParamConstants { param: x * y }.as_std430().as_bytes());
+        compute_pass.dispatch(1, 1, 1);
+    }
+
+    // after the compute pass with encode(), for read() in a later frame
+    pub fn copy(&mut self, encoder: &mut CommandEncoder) {
+        self.readback.copy(encoder, &self.output, 0);
+    }
+
+    // doesn't block, the newest copied result that reached the cpu, if any did since the last call
+    pub fn read(&mut self, wgpu_base: &WgpuBase) -> Option<ReduceResult> {
+        match self.readback.read(wgpu_base)?[..] {
+            [sum, min, max, mean] => Some(ReduceResult {
+                sum,
+                min,
+                max,
+                mean,
+            }),
+            _ => unreachable!(),
+        }
+    }
+}
+
+pub struct Histogram {
+    pub buffer: Buffer, // std430 uint[bins]
+    pub bins: u32,
+    pub range: (f32, f32), // values outside of this are counted in the first or last bin
+    clear: FullComputePipeline,
+    pipeline: FullComputePipeline,
+    param: u32,
+    groups: (u32, u32),
+    readback: AsyncReadback<u32>,
+}
+
+impl Histogram {
+    pub fn encode<'a>(&'a self, compute_pass: &mut ComputePass<'a>) {
+        let (x, y) = self.groups;
+        let (low, high) = self.range;
+
+        compute_pass.begin(&self.clear);
+        compute_pass.pushc(ParamConstants { param: self.bins }.as_std430().as_bytes());
+        compute_pass.dispatch_for(&self.clear, self.bins);
+
+        let constants = HistogramConstants {
+            low,
+            high,
+            bins: self.bins,
+            param: self.param,
+        };
+
+        compute_pass.begin(&self.pipeline);
+        compute_pass.pushc(constants.as_std430().as_bytes());
+        compute_pass.dispatch(x, y, 1);
+    }
+
+    // same as Reduction::copy() and read()
+    pub fn copy(&mut self, encoder: &mut CommandEncoder) {
+        self.readback.copy(encoder, &self.buffer, 0);
+    }
+
+    pub fn read(&mut self, wgpu_base: &WgpuBase) -> Option<Vec<u32>> {
+        self.readback.read(wgpu_base)
+    }
+}
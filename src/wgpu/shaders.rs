@@ -1,25 +1,78 @@
 use fxhash::FxHashMap;
-use wgpu::ShaderModule;
+use wgpu::ShaderStage;
 
 use super::WgpuBase;
+use crate::shaders::{self, LoadedShader, ShaderError};
 
-pub(super) type Shaders = FxHashMap<&'static str, ShaderModule>;
+// keyed by shaders::variant_name
+pub(super) type Shaders = FxHashMap<String, LoadedShader>;
+
+// stand-ins for shaders that failed to load, so that the pipeline can still be created
+// the compute one does nothing and the fragment one draws magenta, vertex shaders have none
+fn fallback(stage: ShaderStage) -> Option<&'static str> {
+    if stage == ShaderStage::COMPUTE {
+        Some("fallback.wgsl:cs_main")
+    } else if stage == ShaderStage::FRAGMENT {
+        Some("fallback.wgsl:fs_main")
+    } else {
+        None
+    }
+}
 
 impl WgpuBase {
+    // checks that the fallbacks load, they are only needed once another shader fails
+    pub(super) fn preload_fallbacks(&mut self) {
+        for &stage in &[ShaderStage::COMPUTE, ShaderStage::FRAGMENT] {
+            if let Some(name) = fallback(stage) {
+                self.shader_preload(name, stage);
+            }
+        }
+    }
+
     // this arrangement is needed because returning a &T from a &mut self method
     // makes the return value borrow over &mut self, which makes it impossible to
     // use &self while the return value is still held
 
-    pub fn shader_preload(&mut self, name: &'static str) {
-        let device = &self.device;
-        self.shaders
-            .entry(name)
-            .or_insert_with(|| crate::shaders::load(device, name));
+    pub fn shader_preload(&mut self, name: &str, stage: ShaderStage) {
+        if self.shaders.contains_key(name) {
+            return;
+        }
+
+        let shader = match shaders::load(&self.device, name) {
+            Ok(shader) => shader,
+            Err(err) => {
+                eprintln!("{}", err);
+
+                let shader = match fallback(stage) {
+                    Some(fallback) => shaders::load(&self.device, fallback)
+                        .unwrap_or_else(|fallback_err| panic!("{}\n{}", err, fallback_err)),
+                    None => panic!("{}", err),
+                };
+
+                self.shader_errors.push(err);
+                shader
+            }
+        };
+
+        self.shaders.insert(name.to_owned(), shader);
     }
 
-    pub fn shader(&self, name: &'static str) -> &ShaderModule {
+    // for sizing buffers by a compute shader's workgroups before its pipeline exists
+    pub fn shader_workgroup_size(&mut self, name: &str) -> [u32; 3] {
+        self.shader_preload(name, ShaderStage::COMPUTE);
+        self.shader(name)
+            .workgroup_size
+            .expect("could not read the workgroup size of the shader")
+    }
+
+    pub fn shader(&self, name: &str) -> &LoadedShader {
         self.shaders
             .get(name)
             .expect("shader not loaded, run preload first")
     }
+
+    // every shader that failed to load so far, these were replaced with a fallback
+    pub fn shader_errors(&self) -> &[ShaderError] {
+        &self.shader_errors
+    }
 }
@@ -1,6 +1,8 @@
-use wgpu::{util::DeviceExt, Texture, TextureDescriptor, TextureView};
+use wgpu::{
+    util::DeviceExt, ImageCopyTexture, Texture, TextureDescriptor, TextureUsage, TextureView,
+};
 
-use crate::util::{texture_size, InitType};
+use crate::util::{texture_image_layout, texture_size, InitType, TextureDesc};
 
 use super::WgpuBase;
 
@@ -22,6 +24,28 @@ impl WgpuBase {
             desc: desc.clone(),
         }
     }
+
+    // overwrite the whole texture, needs TextureUsage::COPY_DST
+    pub fn write_texture(&self, texture: &TextureResult, data: &[u8]) {
+        assert_eq!(texture_size(&texture.desc), data.len());
+
+        self.queue.write_texture(
+            ImageCopyTexture {
+                texture: &texture.texture,
+                mip_level: Default::default(),
+                origin: Default::default(),
+            },
+            data,
+            texture_image_layout(&texture.desc),
+            texture.desc.size,
+        );
+    }
+
+    // intermediate texture for a render pass, that a later pass can sample from
+    pub fn render_target(&self, desc: &TextureDesc) -> TextureResult {
+        let desc = desc.into_2d(TextureUsage::RENDER_ATTACHMENT | TextureUsage::SAMPLED);
+        self.texture(&desc, InitType::Uninit)
+    }
 }
 
 // wgpu does implicit Arc<> semantics in the background, so eg texture can be dropped but view will remain valid
@@ -1,6 +1,7 @@
 use wgpu::{
-    CommandEncoder, PresentMode, RenderPass, Surface, SwapChain, SwapChainDescriptor,
-    SwapChainError, SwapChainTexture, TextureFormat, TextureUsage,
+    Color, CommandEncoder, Operations, PresentMode, RenderPass, Surface, SwapChain,
+    SwapChainDescriptor, SwapChainError, SwapChainTexture, TextureFormat, TextureUsage,
+    TextureView,
 };
 use winit::window::Window;
 
@@ -23,9 +24,26 @@ impl<'a> WgpuWindowed<'a> {
 
         let size = window.inner_size();
 
+        // shaders output linear colors, an srgb swapchain does the encoding for free
+        // users of the swapchain format need to handle both cases (see post::PostStack, ImguiWgpu)
+        // only 8 bit formats, since screenshots are saved as they are (see util::to_image)
+        let format = base
+            .adapter
+            .get_swap_chain_preferred_format(&surface)
+            .filter(|format| {
+                matches!(
+                    format,
+                    TextureFormat::Bgra8Unorm
+                        | TextureFormat::Bgra8UnormSrgb
+                        | TextureFormat::Rgba8Unorm
+                        | TextureFormat::Rgba8UnormSrgb
+                )
+            })
+            .unwrap_or(TextureFormat::Bgra8UnormSrgb);
+
         let swap_chain_desc = SwapChainDescriptor {
             usage: TextureUsage::RENDER_ATTACHMENT,
-            format: TextureFormat::Bgra8Unorm, // adapter.get_swap_chain_preferred_format? // srgb causes linear colors passed in as push constants to be incorrectly lightened
+            format,
             width: size.width,
             height: size.height,
             present_mode: PresentMode::Fifo,
@@ -79,14 +97,30 @@ impl<'a> WgpuWindowed<'a> {
         T: WgpuWindowedRender,
     {
         let texture = self.next_frame()?;
+        self.render_view(&texture.view, target);
+
+        Some(())
+    }
+
+    // one frame onto any view, screenshots use this too so that they prepare and count frames
+    // the same way as the swapchain frames around them
+    pub fn render_view<T>(&mut self, view: &TextureView, target: &mut T)
+    where
+        T: WgpuWindowedRender,
+    {
+        target.prepare(self);
+
+        self.base.write_globals();
 
         let mut helper = HelperRenderTarget {
             wgpu_windowed: self,
             inner: target,
         };
 
-        self.base.render(&texture.view, &mut helper);
-        Some(())
+        self.base.render(view, &mut helper);
+
+        let globals = &mut self.base.globals;
+        globals.frame = globals.frame.wrapping_add(1);
     }
 }
 
@@ -107,6 +141,10 @@ where
         self.inner
             .render_encoder(self.wgpu_windowed, encoder, after);
     }
+
+    fn ops(&self) -> Operations<Color> {
+        self.inner.ops()
+    }
 }
 
 pub trait WgpuWindowedRender {
@@ -117,4 +155,11 @@ pub trait WgpuWindowedRender {
         encoder: &mut CommandEncoder,
         after: bool,
     );
+
+    fn ops(&self) -> Operations<Color> {
+        Default::default()
+    }
+
+    // called before each frame, for changes that need mutable access like creating pipelines
+    fn prepare(&mut self, _wgpu_windowed: &mut WgpuWindowed<'_>) {}
 }
@@ -7,9 +7,19 @@ use winit::{
     window::{Window as WinitWindow, WindowBuilder},
 };
 
+use crate::keymap::{Action, Binding, Keymap};
 use crate::mainloop::Mainloop;
 use crate::util::SafeWgpuSurface;
 
+const QUIT: Action = Action {
+    name: "quit",
+    description: "Quit",
+    default: &[
+        Binding::press(VirtualKeyCode::Escape),
+        Binding::press(VirtualKeyCode::Q),
+    ],
+};
+
 pub struct Window {
     event_loop: EventLoop<()>,
 }
@@ -29,19 +39,37 @@ impl Window {
         T: Mainloop,
     {
         let mut last_frame = Instant::now();
+        let mut keymap = Keymap::new();
 
         self.event_loop.run_return(move |event, _, control_flow| {
             mainloop.event(&event);
 
+            let mut dispatch = |mainloop: &mut T, actions: Vec<&str>| {
+                for action in actions {
+                    if action == QUIT.name {
+                        *control_flow = ControlFlow::Exit;
+                    } else {
+                        mainloop.action(action);
+                    }
+                }
+            };
+
             match event {
                 Event::NewEvents(_) => {
                     let new_frame = Instant::now();
                     let delta_time = new_frame - last_frame;
                     last_frame = new_frame;
 
+                    let mut actions = vec![QUIT];
+                    mainloop.actions(&mut actions);
+                    keymap.set_actions(actions);
+
+                    dispatch(&mut mainloop, keymap.held());
+
                     mainloop.update(delta_time);
                 }
                 Event::RedrawRequested(window_id) if window_id == window.id() => {
+                    mainloop.keymap(&keymap);
                     mainloop.render();
                 }
                 Event::MainEventsCleared => {
@@ -55,20 +83,19 @@ impl Window {
                             new_inner_size: &mut size,
                             ..
                         } => mainloop.resize(size),
+                        WindowEvent::ModifiersChanged(state) => keymap.set_modifiers(state),
+                        // releases always go through, so that keys held before imgui took the keyboard don't get stuck
                         WindowEvent::KeyboardInput {
                             input:
                                 KeyboardInput {
                                     virtual_keycode: Some(key),
-                                    state: ElementState::Pressed,
+                                    state,
                                     ..
                                 },
                             ..
-                        } if !mainloop.ignore_keyboard() => match key {
-                            VirtualKeyCode::Escape | VirtualKeyCode::Q => {
-                                *control_flow = ControlFlow::Exit
-                            }
-                            _ => mainloop.keyboard(key),
-                        },
+                        } if state == ElementState::Released || !mainloop.ignore_keyboard() => {
+                            dispatch(&mut mainloop, keymap.key(key, state));
+                        }
                         _ => {}
                     }
                 }